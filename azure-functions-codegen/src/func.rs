@@ -18,7 +18,7 @@ use std::collections::{HashMap, HashSet};
 use syn::spanned::Spanned;
 use syn::{
     parse, token::Mut, Attribute, AttributeArgs, FnArg, GenericArgument, Ident, ItemFn, Lit, Pat,
-    PathArguments, PathSegment, ReturnType, Type, TypePath, Visibility,
+    PathArguments, PathSegment, ReturnType, Type, TypeParamBound, TypePath, Visibility,
 };
 
 pub const OUTPUT_BINDING_PREFIX: &str = "output";
@@ -27,6 +27,7 @@ const ORCHESTRATION_CONTEXT_TYPE: &str = "DurableOrchestrationContext";
 const ORCHESTRATION_OUTPUT_TYPE: &str = "OrchestrationOutput";
 const ACTIVITY_CONTEXT_TYPE: &str = "DurableActivityContext";
 const ACTIVITY_OUTPUT_TYPE: &str = "ActivityOutput";
+const STATE_TYPE: &str = "State";
 
 fn has_parameter_of_type(func: &ItemFn, type_name: &str) -> bool {
     func.sig.inputs.iter().any(|arg| {
@@ -188,6 +189,91 @@ fn validate_function(func: &ItemFn) {
     }
 }
 
+// Validates a `State<T>` parameter, returning the state type `T`.
+fn validate_state_argument(arg: &FnArg) -> &Type {
+    let ty = match arg {
+        FnArg::Typed(arg) => &*arg.ty,
+        FnArg::Receiver(_) => {
+            macro_panic(arg.span(), "Azure Functions cannot have self parameters")
+        }
+    };
+
+    let tp = match ty {
+        Type::Path(tp) => tp,
+        Type::Paren(tp) => match &*tp.elem {
+            Type::Path(tp) => tp,
+            _ => macro_panic(ty.span(), "expected a state parameter of type `State<T>`"),
+        },
+        _ => macro_panic(
+            ty.span(),
+            "state parameters must be passed by value as `State<T>`",
+        ),
+    };
+
+    let state_type = match get_generic_argument_type(last_segment_in_path(&tp.path), STATE_TYPE) {
+        Some(t) => t,
+        None => macro_panic(
+            tp.span(),
+            "state parameters must have exactly one type argument (e.g. `State<Pool>`)",
+        ),
+    };
+
+    fn is_concrete(ty: &Type) -> bool {
+        match ty {
+            Type::Path(tp) => {
+                tp.qself.is_none()
+                    && tp.path.segments.iter().all(|s| match &s.arguments {
+                        PathArguments::None => true,
+                        PathArguments::AngleBracketed(args) => args.args.iter().all(|a| match a {
+                            GenericArgument::Type(t) => is_concrete(t),
+                            GenericArgument::Lifetime(l) => l.ident == "static",
+                            _ => false,
+                        }),
+                        PathArguments::Parenthesized(_) => false,
+                    })
+            }
+            Type::Paren(tp) => is_concrete(&tp.elem),
+            Type::Tuple(tuple) => tuple.elems.iter().all(is_concrete),
+            Type::Array(array) => is_concrete(&array.elem),
+            Type::TraitObject(to) => to.bounds.iter().all(|b| match b {
+                TypeParamBound::Trait(_) => true,
+                TypeParamBound::Lifetime(l) => l.ident == "static",
+            }),
+            _ => false,
+        }
+    }
+
+    // State is resolved by type, so the type must be fully specified and 'static
+    if !is_concrete(state_type) {
+        macro_panic(
+            state_type.span(),
+            "ambiguous state type; state types must be concrete types without references, lifetimes or placeholders",
+        );
+    }
+
+    state_type
+}
+
+// Validates the `State<T>` parameters of a function, which must each have a different state type.
+fn validate_state_arguments(func: &ItemFn) {
+    let mut state_types = HashSet::new();
+    for arg in &func.sig.inputs {
+        if let FnArg::Typed(typed) = arg {
+            if !Invoker::is_state_type(&typed.ty) {
+                continue;
+            }
+
+            let state_type = validate_state_argument(arg);
+            if !state_types.insert(quote!(#state_type).to_string()) {
+                macro_panic(
+                    arg.span(),
+                    "a function cannot have more than one parameter of the same state type",
+                );
+            }
+        }
+    }
+}
+
 fn get_generic_argument_type<'a>(
     last: &'a PathSegment,
     generic_type_name: &str,
@@ -210,6 +296,33 @@ fn get_generic_argument_type<'a>(
     }
 }
 
+// Gets the `T` of a `Result<T, E>` return type; the error type is not bound.
+//
+// Only a `Result` with two type arguments is recognized, as aliases such as `io::Result<T>`
+// cannot be resolved by the macro.
+fn get_result_ok_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(tp) => {
+            let last = last_segment_in_path(&tp.path);
+            if last.ident != "Result" {
+                return None;
+            }
+
+            match &last.arguments {
+                PathArguments::AngleBracketed(gen_args) if gen_args.args.len() == 2 => {
+                    match (&gen_args.args[0], &gen_args.args[1]) {
+                        (GenericArgument::Type(t), GenericArgument::Type(_)) => Some(t),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        Type::Paren(tp) => get_result_ok_type(&tp.elem),
+        _ => None,
+    }
+}
+
 fn get_input_binding_factory_for_vec(tp: &TypePath, mutability: Option<Mut>) -> &BindingFactory {
     let last_segment = last_segment_in_path(&tp.path);
     let type_name = last_segment.ident.to_string();
@@ -426,7 +539,9 @@ fn bind_return_type(
     let mut bindings = Vec::new();
 
     if let ReturnType::Type(_, ty) = ret {
-        if let Type::Tuple(tuple) = &**ty {
+        let ty = get_result_ok_type(ty).unwrap_or(ty);
+
+        if let Type::Tuple(tuple) = ty {
             for (i, ty) in tuple.elems.iter().enumerate() {
                 if let Type::Tuple(inner) = ty {
                     if !inner.elems.is_empty() {
@@ -514,6 +629,7 @@ pub fn func_impl(
     };
 
     validate_function(&target);
+    validate_state_arguments(&target);
 
     let is_orchestration = has_parameter_of_type(&target, ORCHESTRATION_CONTEXT_TYPE);
     let is_activity = has_parameter_of_type(&target, ACTIVITY_CONTEXT_TYPE);
@@ -536,6 +652,13 @@ pub fn func_impl(
     let mut names = HashSet::new();
     let mut has_trigger = false;
     for arg in &target.sig.inputs {
+        if let FnArg::Typed(typed) = arg {
+            // State parameters are not bindings
+            if Invoker::is_state_type(&typed.ty) {
+                continue;
+            }
+        }
+
         let binding = bind_argument(&arg, has_trigger, &mut binding_args);
         has_trigger |= binding.is_trigger();
 
@@ -650,3 +773,144 @@ pub fn func_impl(
     )
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{catch_unwind, UnwindSafe};
+    use syn::parse_str;
+
+    fn should_panic<T>(callback: T, msg: &str)
+    where
+        T: FnOnce() + UnwindSafe,
+    {
+        let result = catch_unwind(callback);
+        assert!(result.is_err(), "the function did not panic");
+
+        if cfg!(feature = "unstable") {
+            assert_eq!(
+                result.unwrap_err().downcast_ref::<String>().unwrap(),
+                "aborting due to previous error",
+                "the panic message is not the expected one"
+            );
+        } else {
+            assert_eq!(
+                result.unwrap_err().downcast_ref::<String>().unwrap(),
+                msg,
+                "the panic message is not the expected one"
+            );
+        }
+    }
+
+    #[test]
+    fn it_gets_the_ok_type_of_a_result() {
+        for (ty, ok) in &[
+            ("Result<HttpResponse, Error>", "HttpResponse"),
+            (
+                "std::result::Result<(HttpResponse, Blob), Box<dyn Error>>",
+                "(HttpResponse , Blob)",
+            ),
+            ("(Result<HttpResponse, Error>)", "HttpResponse"),
+        ] {
+            let ty = parse_str::<Type>(ty).unwrap();
+            let ok_type = get_result_ok_type(&ty).unwrap();
+            assert_eq!(&quote!(#ok_type).to_string(), ok);
+        }
+
+        for ty in &[
+            "io::Result<HttpResponse>",
+            "Result<HttpResponse, 'a>",
+            "HttpResponse",
+        ] {
+            assert!(
+                get_result_ok_type(&parse_str::<Type>(ty).unwrap()).is_none(),
+                "{}",
+                ty
+            );
+        }
+    }
+
+    #[test]
+    fn it_matches_state_parameter_types() {
+        for ty in &[
+            "State<Pool>",
+            "&State<Pool>",
+            "state::State<Pool>",
+            "azure_functions::state::State<Pool>",
+            "::azure_functions::state::State<Pool>",
+        ] {
+            assert!(Invoker::is_state_type(&parse_str(ty).unwrap()), "{}", ty);
+        }
+
+        for ty in &[
+            "MyState<Pool>",
+            "other::State<Pool>",
+            "azure_functions::State<Pool>",
+        ] {
+            assert!(!Invoker::is_state_type(&parse_str(ty).unwrap()), "{}", ty);
+        }
+    }
+
+    #[test]
+    fn it_gets_the_state_type() {
+        let arg = parse_str::<FnArg>("pool: State<db::Pool>").unwrap();
+        let ty = validate_state_argument(&arg);
+        assert_eq!(quote!(#ty).to_string(), "db :: Pool");
+    }
+
+    #[test]
+    fn it_requires_state_be_passed_by_value() {
+        should_panic(
+            || {
+                validate_state_argument(&parse_str::<FnArg>("pool: &State<Pool>").unwrap());
+            },
+            "state parameters must be passed by value as `State<T>`",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_state_type_argument() {
+        should_panic(
+            || {
+                validate_state_argument(&parse_str::<FnArg>("pool: State").unwrap());
+            },
+            "state parameters must have exactly one type argument (e.g. `State<Pool>`)",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_concrete_state_type() {
+        for ty in &["State<_>", "State<&'static Pool>", "State<Vec<&'a str>>"] {
+            should_panic(
+                || {
+                    validate_state_argument(
+                        &parse_str::<FnArg>(&format!("pool: {}", ty)).unwrap(),
+                    );
+                },
+                "ambiguous state type; state types must be concrete types without references, lifetimes or placeholders",
+            );
+        }
+    }
+
+    #[test]
+    fn it_requires_distinct_state_types() {
+        validate_state_arguments(
+            &parse_str::<ItemFn>(
+                "fn f(req: HttpRequest, pool: State<Pool>, cache: State<Cache>) {}",
+            )
+            .unwrap(),
+        );
+
+        should_panic(
+            || {
+                validate_state_arguments(
+                    &parse_str::<ItemFn>(
+                        "fn f(req: HttpRequest, pool: State<Pool>, other: state::State<Pool>) {}",
+                    )
+                    .unwrap(),
+                );
+            },
+            "a function cannot have more than one parameter of the same state type",
+        );
+    }
+}
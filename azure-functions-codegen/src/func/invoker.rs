@@ -6,6 +6,12 @@ use quote::{quote, ToTokens};
 use syn::{FnArg, Ident, ItemFn, Pat, Type};
 
 const INVOKER_PREFIX: &str = "__invoke_";
+// The paths a `State<T>` parameter type can be written with
+const STATE_PATHS: &[&[&str]] = &[
+    &["State"],
+    &["state", "State"],
+    &["azure_functions", "state", "State"],
+];
 
 pub struct Invoker<'a> {
     pub func: &'a ItemFn,
@@ -33,6 +39,25 @@ impl<'a> Invoker<'a> {
             _ => false,
         }
     }
+
+    pub fn is_state_type(ty: &Type) -> bool {
+        match Invoker::deref_arg_type(ty) {
+            Type::Path(tp) => {
+                tp.qself.is_none()
+                    && STATE_PATHS.iter().any(|path| {
+                        tp.path.segments.len() == path.len()
+                            && tp
+                                .path
+                                .segments
+                                .iter()
+                                .zip(path.iter())
+                                .all(|(segment, name)| segment.ident == name)
+                    })
+            }
+            Type::Paren(tp) => Invoker::is_state_type(&tp.elem),
+            _ => false,
+        }
+    }
 }
 
 struct CommonInvokerTokens<'a> {
@@ -44,7 +69,7 @@ impl<'a> CommonInvokerTokens<'a> {
     fn get_input_args(&self) -> (Vec<&'a Ident>, Vec<&'a Type>) {
         self.iter_args()
             .filter_map(|(name, arg_type)| {
-                if Invoker::is_trigger_type(arg_type) {
+                if Invoker::is_trigger_type(arg_type) || Invoker::is_state_type(arg_type) {
                     return None;
                 }
 
@@ -56,21 +81,21 @@ impl<'a> CommonInvokerTokens<'a> {
     fn get_input_assignments(&self) -> Vec<TokenStream> {
         self.iter_args()
             .filter_map(|(_, arg_type)| {
-                if Invoker::is_trigger_type(arg_type) {
+                if Invoker::is_trigger_type(arg_type) || Invoker::is_state_type(arg_type) {
                     return None;
                 }
 
                 if let Type::Path(tp) = Invoker::deref_arg_type(arg_type) {
                     if get_generic_argument_type(last_segment_in_path(&tp.path), "Vec").is_some() {
                         return Some(quote!(__param
-                            .data
+                            .into_data()
                             .expect("expected parameter binding data")
                             .into_vec()));
                     }
                 }
 
                 Some(quote!(__param
-                    .data
+                    .into_data()
                     .expect("expected parameter binding data")
                     .into()))
             })
@@ -91,11 +116,44 @@ impl<'a> CommonInvokerTokens<'a> {
         }
     }
 
+    // Resolves the state parameters, returning a failed invocation if a state is not registered
+    fn get_state_resolutions(&self) -> Vec<TokenStream> {
+        let fail = if !self.is_orchestration && self.func.sig.asyncness.is_some() {
+            quote!(return Box::pin(::futures::future::ready(__res)))
+        } else {
+            quote!(return __res)
+        };
+
+        self.iter_args()
+            .filter(|(_, arg_type)| Invoker::is_state_type(arg_type))
+            .map(|(name, _)| {
+                quote!(
+                    let #name = match ::azure_functions::state::State::resolve() {
+                        Ok(__state) => __state,
+                        Err(__err) => {
+                            let __res = ::azure_functions::rpc::InvocationResponse {
+                                invocation_id: __req.invocation_id,
+                                result: Some(::azure_functions::failure(__err)),
+                                ..Default::default()
+                            };
+                            #fail;
+                        }
+                    };
+                )
+            })
+            .collect()
+    }
+
     fn get_args_for_call(&self) -> Vec<TokenStream> {
         self.iter_args()
             .map(|(name, arg_type)| {
                 let name_str = name.to_string();
 
+                // State is resolved from the state registered with the worker rather than a binding
+                if Invoker::is_state_type(arg_type) {
+                    return quote!(#name);
+                }
+
                 if let Type::Reference(tr) = arg_type {
                     return match tr.mutability {
                         Some(_) => quote!(#name.as_mut().expect(concat!("parameter binding '", #name_str, "' was not provided"))),
@@ -139,10 +197,13 @@ impl ToTokens for CommonInvokerTokens<'_> {
         let args_for_call = self.get_args_for_call();
 
         let state_arg = self.get_state_arg(trigger_arg);
+        let state_resolutions = self.get_state_resolutions();
 
         quote!(
             use azure_functions::{IntoVec, FromVec};
 
+            #(#state_resolutions)*
+
             let mut #trigger_arg: Option<#trigger_type> = None;
             #(let mut #args: Option<#types> = None;)*
 
@@ -152,7 +213,7 @@ impl ToTokens for CommonInvokerTokens<'_> {
                 match __param.name.as_str() {
                     #trigger_name => #trigger_arg = Some(
                         #trigger_type::new(
-                            __param.data.expect("expected parameter binding data"),
+                            __param.into_data().expect("expected parameter binding data"),
                             __metadata.take().expect("expected only one trigger"),
                         )
                     ),
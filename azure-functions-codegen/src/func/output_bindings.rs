@@ -1,4 +1,4 @@
-use crate::func::get_generic_argument_type;
+use crate::func::{get_generic_argument_type, get_result_ok_type};
 use azure_functions_shared::{codegen::last_segment_in_path, util::to_camel_case};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
@@ -15,10 +15,10 @@ impl<'a> OutputBindings<'a> {
             .map(|(name, _)| {
                 let name_str = to_camel_case(&name.to_string());
                 quote!(
-                    __res.output_data.push(::azure_functions::rpc::ParameterBinding{
-                        name: #name_str.to_string(),
-                        data: Some(#name.unwrap().into()),
-                    });
+                    __res.output_data.push(::azure_functions::rpc::ParameterBinding::new(
+                        #name_str,
+                        #name.unwrap().into(),
+                    ));
                 )
             })
             .collect()
@@ -36,20 +36,20 @@ impl<'a> OutputBindings<'a> {
                 let conversion = OutputBindings::get_binding_conversion(inner, None);
                 Some(quote!(
                     if let Some(__ret) = __ret.#index {
-                        __res.output_data.push(::azure_functions::rpc::ParameterBinding{
-                            name: #name.to_string(),
-                            data: Some(#conversion)
-                        });
+                        __res.output_data.push(::azure_functions::rpc::ParameterBinding::new(
+                            #name,
+                            #conversion,
+                        ));
                     }
                 ))
             }
             None => {
                 let conversion = OutputBindings::get_binding_conversion(ty, Some(index));
                 Some(quote!(
-                    __res.output_data.push(::azure_functions::rpc::ParameterBinding{
-                        name: #name.to_string(),
-                        data: Some(#conversion)
-                    });
+                    __res.output_data.push(::azure_functions::rpc::ParameterBinding::new(
+                        #name,
+                        #conversion,
+                    ));
                 ))
             }
         }
@@ -68,19 +68,30 @@ impl<'a> OutputBindings<'a> {
         }
     }
 
-    fn iter_output_return_bindings(&self) -> Vec<TokenStream> {
+    fn get_return_type(&self) -> Option<&'a Type> {
         match &self.func.sig.output {
-            ReturnType::Default => vec![],
-            ReturnType::Type(_, ty) => match &**ty {
-                Type::Tuple(tuple) => tuple
-                    .elems
-                    .iter()
-                    .enumerate()
-                    .skip(1)
-                    .filter_map(|(i, ty)| OutputBindings::get_output_return_binding(ty, i.into()))
-                    .collect(),
-                _ => vec![],
-            },
+            ReturnType::Default => None,
+            ReturnType::Type(_, ty) => Some(get_result_ok_type(ty).unwrap_or(ty)),
+        }
+    }
+
+    fn is_result(&self) -> bool {
+        match &self.func.sig.output {
+            ReturnType::Default => false,
+            ReturnType::Type(_, ty) => get_result_ok_type(ty).is_some(),
+        }
+    }
+
+    fn iter_output_return_bindings(&self) -> Vec<TokenStream> {
+        match self.get_return_type() {
+            Some(Type::Tuple(tuple)) => tuple
+                .elems
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter_map(|(i, ty)| OutputBindings::get_output_return_binding(ty, i.into()))
+                .collect(),
+            _ => vec![],
         }
     }
 
@@ -175,21 +186,36 @@ impl ToTokens for OutputBindings<'_> {
             return;
         }
 
+        let mut bindings = TokenStream::new();
+
         for binding in self.get_output_argument_bindings() {
-            binding.to_tokens(tokens);
+            binding.to_tokens(&mut bindings);
         }
 
         for binding in self.iter_output_return_bindings() {
-            binding.to_tokens(tokens);
+            binding.to_tokens(&mut bindings);
         }
 
-        match &self.func.sig.output {
-            ReturnType::Default => {}
-            ReturnType::Type(_, ty) => {
-                if let Some(binding) = OutputBindings::get_return_binding(ty, false) {
-                    binding.to_tokens(tokens);
-                }
+        if let Some(ty) = self.get_return_type() {
+            if let Some(binding) = OutputBindings::get_return_binding(ty, false) {
+                binding.to_tokens(&mut bindings);
             }
         }
+
+        // Functions returning an error fail the invocation without setting any output bindings
+        if self.is_result() {
+            quote!(
+                match __ret {
+                    #[allow(unused_variables)]
+                    Ok(__ret) => { #bindings }
+                    Err(__err) => {
+                        __res.result = Some(::azure_functions::failure(__err));
+                    }
+                }
+            )
+            .to_tokens(tokens);
+        } else {
+            bindings.to_tokens(tokens);
+        }
     }
 }
@@ -14,6 +14,7 @@ pub struct Run<'a> {
     port: Option<&'a str>,
     script_root: Option<&'a str>,
     no_debug_info: bool,
+    standalone: bool,
     cargo_options: Option<Values<'a>>,
 }
 
@@ -56,6 +57,13 @@ impl<'a> Run<'a> {
                     .long("--no-debug-info")
                     .help("Do not copy debug information for the worker executable.")
             )
+            .arg(
+                Arg::with_name("standalone")
+                    .long("standalone")
+                    .help("Run HTTP-triggered functions with a built-in development server instead of the Azure Functions Host.")
+                    .conflicts_with("script_root")
+                    .conflicts_with("no_debug_info"),
+            )
             .arg(Arg::with_name("cargo_options")
                 .multiple(true)
                 .value_name("CARGO_OPTIONS")
@@ -77,6 +85,10 @@ impl<'a> Run<'a> {
 
         self.set_colorization();
 
+        if self.standalone {
+            return self.run_dev_server();
+        }
+
         let (_temp_dir, script_root) = match self.script_root {
             Some(dir) => {
                 let script_root = current_dir()
@@ -152,6 +164,49 @@ impl<'a> Run<'a> {
         Ok(())
     }
 
+    fn run_dev_server(&self) -> Result<(), String> {
+        let mut args = vec!["run"];
+
+        if let Some(values) = self.cargo_options.as_ref() {
+            for value in values.clone() {
+                args.push(value);
+            }
+        }
+
+        args.extend_from_slice(&["--", "serve-http", "--port", self.port.unwrap_or("8080")]);
+
+        if !self.quiet {
+            print_running(&format!(
+                "spawning 'cargo' to start the development server: {}",
+                format!("cargo {}", args.join(" ")).cyan()
+            ));
+        }
+
+        let mut child = Command::new("cargo").args(&args).spawn().map_err(|e| {
+            if !self.quiet {
+                print_failure();
+            }
+            format!("failed to spawn cargo: {}", e)
+        })?;
+
+        if !self.quiet {
+            print_success();
+        }
+
+        let status = child
+            .wait()
+            .map_err(|e| format!("failed to wait for cargo: {}", e))?;
+
+        if !status.success() {
+            return Err(format!(
+                "cargo failed with exit code {}.",
+                status.code().unwrap()
+            ));
+        }
+
+        Ok(())
+    }
+
     fn run_host(&self, script_root: &Path) -> Result<(), String> {
         let args = ["host", "start", "--port", self.port.unwrap_or("8080")];
 
@@ -200,6 +255,7 @@ impl<'a> From<&'a ArgMatches> for Run<'a> {
             port: args.value_of("port"),
             script_root: args.value_of("script_root"),
             no_debug_info: args.is_present("no_debug_info"),
+            standalone: args.is_present("standalone"),
             cargo_options: args.values_of("cargo_options"),
         }
     }
@@ -17,7 +17,7 @@ fn format_source(path: &Path) {
 
 fn compile_protobufs(out_dir: &PathBuf, cache_dir: &PathBuf) {
     tonic_build::configure()
-        .build_server(false)
+        .build_server(true)
         .compile(PROTOBUF_INPUT_FILES, &["protobuf/src/proto"])
         .unwrap_or_else(|e| panic!("protobuf compilation failed: {}", e));
 
@@ -82,7 +82,7 @@ pub struct StreamingMessage {
     #[prost(string, tag="1")]
     pub request_id: ::prost::alloc::string::String,
     /// Payload of the message
-    #[prost(oneof="streaming_message::Content", tags="20, 17, 16, 15, 14, 12, 13, 6, 7, 8, 9, 4, 5, 21, 2, 25, 26")]
+    #[prost(oneof="streaming_message::Content", tags="20, 17, 16, 15, 14, 12, 13, 6, 7, 8, 9, 4, 5, 21, 2, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34")]
     pub content: ::core::option::Option<streaming_message::Content>,
 }
 /// Nested message and enum types in `StreamingMessage`.
@@ -140,6 +140,28 @@ pub mod streaming_message {
         FunctionEnvironmentReloadRequest(super::FunctionEnvironmentReloadRequest),
         #[prost(message, tag="26")]
         FunctionEnvironmentReloadResponse(super::FunctionEnvironmentReloadResponse),
+        /// Ask the worker to close any open shared memory resources for a given invocation
+        #[prost(message, tag="27")]
+        CloseSharedMemoryResourcesRequest(super::CloseSharedMemoryResourcesRequest),
+        #[prost(message, tag="28")]
+        CloseSharedMemoryResourcesResponse(super::CloseSharedMemoryResourcesResponse),
+        /// Worker indexing message types
+        #[prost(message, tag="29")]
+        FunctionsMetadataRequest(super::FunctionsMetadataRequest),
+        #[prost(message, tag="30")]
+        FunctionMetadataResponse(super::FunctionMetadataResponse),
+        /// Host sends required metadata to worker to load functions
+        #[prost(message, tag="31")]
+        FunctionLoadRequestCollection(super::FunctionLoadRequestCollection),
+        /// Host gets the list of function load responses
+        #[prost(message, tag="32")]
+        FunctionLoadResponseCollection(super::FunctionLoadResponseCollection),
+        /// Host sends required metadata to worker to warmup the worker
+        #[prost(message, tag="33")]
+        WorkerWarmupRequest(super::WorkerWarmupRequest),
+        /// Worker responds after warming up with the warmup result
+        #[prost(message, tag="34")]
+        WorkerWarmupResponse(super::WorkerWarmupResponse),
     }
 }
 // Process.Start required info
@@ -167,6 +189,12 @@ pub struct WorkerInitRequest {
     /// i.e. Worker = Verbose, Function.MyFunc = None
     #[prost(map="string, enumeration(rpc_log::Level)", tag="3")]
     pub log_categories: ::std::collections::HashMap<::prost::alloc::string::String, i32>,
+    /// Full path of worker.config.json location
+    #[prost(string, tag="4")]
+    pub worker_directory: ::prost::alloc::string::String,
+    /// base directory for function app
+    #[prost(string, tag="5")]
+    pub function_app_directory: ::prost::alloc::string::String,
 }
 /// Worker responds with the result of initializing itself
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -180,6 +208,27 @@ pub struct WorkerInitResponse {
     /// Status of the response
     #[prost(message, optional, tag="3")]
     pub result: ::core::option::Option<StatusResult>,
+    /// Worker metadata captured for telemetry purposes
+    #[prost(message, optional, tag="4")]
+    pub worker_metadata: ::core::option::Option<WorkerMetadata>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerMetadata {
+    /// The runtime/stack name
+    #[prost(string, tag="1")]
+    pub runtime_name: ::prost::alloc::string::String,
+    /// The version of the runtime/stack
+    #[prost(string, tag="2")]
+    pub runtime_version: ::prost::alloc::string::String,
+    /// The version of the worker
+    #[prost(string, tag="3")]
+    pub worker_version: ::prost::alloc::string::String,
+    /// The worker bitness/architecture
+    #[prost(string, tag="4")]
+    pub worker_bitness: ::prost::alloc::string::String,
+    /// Optional additional custom properties
+    #[prost(map="string, string", tag="5")]
+    pub custom_properties: ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
 }
 /// Used by the host to determine success/failure/cancellation
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -276,18 +325,66 @@ pub struct WorkerStatusRequest {
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WorkerStatusResponse {
 }
+/// Warmup request sent by the host to the worker
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerWarmupRequest {
+    /// Full path of worker.config.json location
+    #[prost(string, tag="1")]
+    pub worker_directory: ::prost::alloc::string::String,
+}
+/// Worker responds with the result of the warmup
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerWarmupResponse {
+    #[prost(message, optional, tag="1")]
+    pub result: ::core::option::Option<StatusResult>,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FunctionEnvironmentReloadRequest {
     /// Environment variables from the current process
     #[prost(map="string, string", tag="1")]
     pub environment_variables: ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    /// Current directory of function app
+    #[prost(string, tag="2")]
+    pub function_app_directory: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FunctionEnvironmentReloadResponse {
+    /// After specialization, worker sends capabilities & metadata.
+    /// Worker metadata captured for telemetry purposes
+    #[prost(message, optional, tag="1")]
+    pub worker_metadata: ::core::option::Option<WorkerMetadata>,
+    /// A map of worker supported features/capabilities
+    #[prost(map="string, string", tag="2")]
+    pub capabilities: ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
     /// Status of the response
     #[prost(message, optional, tag="3")]
     pub result: ::core::option::Option<StatusResult>,
 }
+/// Tell the out-of-proc worker to close any shared memory maps it allocated for given invocation
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CloseSharedMemoryResourcesRequest {
+    #[prost(string, repeated, tag="1")]
+    pub map_names: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// Response from the worker indicating which of the shared memory maps have been successfully closed and which have not been closed
+/// The key (string) is the map name and the value (bool) is true if it was closed, false if not
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CloseSharedMemoryResourcesResponse {
+    #[prost(map="string, bool", tag="1")]
+    pub close_map_results: ::std::collections::HashMap<::prost::alloc::string::String, bool>,
+}
+/// Host tells the worker to load a list of Functions
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FunctionLoadRequestCollection {
+    #[prost(message, repeated, tag="1")]
+    pub function_load_requests: ::prost::alloc::vec::Vec<FunctionLoadRequest>,
+}
+/// Host gets the list of function load responses
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FunctionLoadResponseCollection {
+    #[prost(message, repeated, tag="1")]
+    pub function_load_responses: ::prost::alloc::vec::Vec<FunctionLoadResponse>,
+}
 /// Host tells the worker to load a Function
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FunctionLoadRequest {
@@ -337,6 +434,49 @@ pub struct RpcFunctionMetadata {
     /// Is set to true for proxy
     #[prost(bool, tag="7")]
     pub is_proxy: bool,
+    /// Function indexing status
+    #[prost(message, optional, tag="8")]
+    pub status: ::core::option::Option<StatusResult>,
+    /// Function language
+    #[prost(string, tag="9")]
+    pub language: ::prost::alloc::string::String,
+    /// Raw binding info
+    #[prost(string, repeated, tag="10")]
+    pub raw_bindings: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// unique function identifier (avoid name collisions, facilitate reload case)
+    #[prost(string, tag="13")]
+    pub function_id: ::prost::alloc::string::String,
+    /// A flag indicating if managed dependency is enabled or not
+    #[prost(bool, tag="14")]
+    pub managed_dependency_enabled: bool,
+    /// The optional function execution retry strategy to use on invocation failures.
+    #[prost(message, optional, tag="15")]
+    pub retry_options: ::core::option::Option<RpcRetryOptions>,
+    /// Properties for function metadata
+    /// They're usually specific to a worker and largely passed along to the controller API for use
+    /// outside the host
+    #[prost(map="string, string", tag="16")]
+    pub properties: ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+}
+/// Host tells worker it is ready to receive metadata
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FunctionsMetadataRequest {
+    /// base directory for function app
+    #[prost(string, tag="1")]
+    pub function_app_directory: ::prost::alloc::string::String,
+}
+/// Worker sends function metadata back to host
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FunctionMetadataResponse {
+    /// list of function indexing responses
+    #[prost(message, repeated, tag="1")]
+    pub function_metadata_results: ::prost::alloc::vec::Vec<RpcFunctionMetadata>,
+    /// status of overall metadata request
+    #[prost(message, optional, tag="2")]
+    pub result: ::core::option::Option<StatusResult>,
+    /// if set to true then host will perform indexing
+    #[prost(bool, tag="3")]
+    pub use_default_metadata_indexing: bool,
 }
 /// Host requests worker to invoke a Function
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -353,6 +493,38 @@ pub struct InvocationRequest {
     /// binding metadata from trigger
     #[prost(map="string, message", tag="4")]
     pub trigger_metadata: ::std::collections::HashMap<::prost::alloc::string::String, TypedData>,
+    /// Populates activityId, tracestate and tags from host
+    #[prost(message, optional, tag="5")]
+    pub trace_context: ::core::option::Option<RpcTraceContext>,
+    /// Current retry context
+    #[prost(message, optional, tag="6")]
+    pub retry_context: ::core::option::Option<RetryContext>,
+}
+/// Host sends ActivityId, traceStateString and Tags from host
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RpcTraceContext {
+    /// This corresponds to Activity.Current?.Id
+    #[prost(string, tag="1")]
+    pub trace_parent: ::prost::alloc::string::String,
+    /// This corresponds to Activity.Current?.TraceStateString
+    #[prost(string, tag="2")]
+    pub trace_state: ::prost::alloc::string::String,
+    /// This corresponds to Activity.Current?.Tags
+    #[prost(map="string, string", tag="3")]
+    pub attributes: ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+}
+/// Host sends retry context for a function invocation
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetryContext {
+    /// Current retry count
+    #[prost(int32, tag="1")]
+    pub retry_count: i32,
+    /// Max retry count
+    #[prost(int32, tag="2")]
+    pub max_retry_count: i32,
+    /// Exception that caused the retry
+    #[prost(message, optional, tag="3")]
+    pub exception: ::core::option::Option<RpcException>,
 }
 /// Host requests worker to cancel invocation
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -385,7 +557,7 @@ pub struct InvocationResponse {
 /// Used to encapsulate data which could be a variety of types
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TypedData {
-    #[prost(oneof="typed_data::Data", tags="1, 2, 3, 4, 5, 6, 7")]
+    #[prost(oneof="typed_data::Data", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13")]
     pub data: ::core::option::Option<typed_data::Data>,
 }
 /// Nested message and enum types in `TypedData`.
@@ -406,17 +578,80 @@ pub mod typed_data {
         Int(i64),
         #[prost(double, tag="7")]
         Double(f64),
+        #[prost(message, tag="8")]
+        CollectionBytes(super::CollectionBytes),
+        #[prost(message, tag="9")]
+        CollectionString(super::CollectionString),
+        #[prost(message, tag="10")]
+        CollectionDouble(super::CollectionDouble),
+        #[prost(message, tag="11")]
+        CollectionSint64(super::CollectionSInt64),
+        #[prost(message, tag="12")]
+        ModelBindingData(super::ModelBindingData),
+        #[prost(message, tag="13")]
+        CollectionModelBindingData(super::CollectionModelBindingData),
     }
 }
+/// Used to encapsulate memory-mapped data
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RpcSharedMemory {
+    /// Name of the shared memory map containing data
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    /// Offset in the shared memory map to start reading data from
+    #[prost(int64, tag="2")]
+    pub offset: i64,
+    /// Number of bytes to read (starting from the offset)
+    #[prost(int64, tag="3")]
+    pub count: i64,
+    /// Final type to which the read data (in bytes) is to be interpreted as
+    #[prost(enumeration="RpcDataType", tag="4")]
+    pub r#type: i32,
+}
+/// Used to encapsulate collection string
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionString {
+    #[prost(string, repeated, tag="1")]
+    pub string: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// Used to encapsulate collection bytes
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionBytes {
+    #[prost(bytes="vec", repeated, tag="1")]
+    pub bytes: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// Used to encapsulate collection double
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionDouble {
+    #[prost(double, repeated, tag="1")]
+    pub double: ::prost::alloc::vec::Vec<f64>,
+}
+/// Used to encapsulate collection sint64
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionSInt64 {
+    #[prost(sint64, repeated, tag="1")]
+    pub sint64: ::prost::alloc::vec::Vec<i64>,
+}
 /// Used to describe a given binding on invocation
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ParameterBinding {
     /// Name for the binding
     #[prost(string, tag="1")]
     pub name: ::prost::alloc::string::String,
-    /// Data for the binding
-    #[prost(message, optional, tag="2")]
-    pub data: ::core::option::Option<TypedData>,
+    #[prost(oneof="parameter_binding::RpcData", tags="2, 3")]
+    pub rpc_data: ::core::option::Option<parameter_binding::RpcData>,
+}
+/// Nested message and enum types in `ParameterBinding`.
+pub mod parameter_binding {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum RpcData {
+        /// Data for the binding
+        #[prost(message, tag="2")]
+        Data(super::TypedData),
+        /// Metadata about the shared memory region to read data from
+        #[prost(message, tag="3")]
+        RpcSharedMemory(super::RpcSharedMemory),
+    }
 }
 /// Used to describe a given binding on load
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -429,6 +664,9 @@ pub struct BindingInfo {
     pub direction: i32,
     #[prost(enumeration="binding_info::DataType", tag="4")]
     pub data_type: i32,
+    /// Properties for binding metadata
+    #[prost(map="string, string", tag="5")]
+    pub properties: ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
 }
 /// Nested message and enum types in `BindingInfo`.
 pub mod binding_info {
@@ -472,9 +710,15 @@ pub struct RpcLog {
     /// Exception (if exists)
     #[prost(message, optional, tag="6")]
     pub exception: ::core::option::Option<RpcException>,
-    /// json serialized property bag, or could use a type scheme like map<string, TypedData>
+    /// json serialized property bag
     #[prost(string, tag="7")]
     pub properties: ::prost::alloc::string::String,
+    /// Category of the log. Defaults to User if not specified.
+    #[prost(enumeration="rpc_log::RpcLogCategory", tag="8")]
+    pub log_category: i32,
+    /// strongly-typed (ish) property bag
+    #[prost(map="string, message", tag="9")]
+    pub properties_map: ::std::collections::HashMap<::prost::alloc::string::String, TypedData>,
 }
 /// Nested message and enum types in `RpcLog`.
 pub mod rpc_log {
@@ -492,6 +736,14 @@ pub mod rpc_log {
         Critical = 5,
         None = 6,
     }
+    /// Category of the log. Defaults to User if not specified.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum RpcLogCategory {
+        User = 0,
+        System = 1,
+        CustomMetric = 2,
+    }
 }
 /// Encapsulates an Exception 
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -502,9 +754,17 @@ pub struct RpcException {
     /// Stack trace for the exception
     #[prost(string, tag="1")]
     pub stack_trace: ::prost::alloc::string::String,
-    /// Textual message describing hte exception
+    /// Textual message describing the exception
     #[prost(string, tag="2")]
     pub message: ::prost::alloc::string::String,
+    /// Worker specifies whether exception is a user exception,
+    /// for purpose of application insights logging. Defaults to false.
+    #[prost(bool, tag="4")]
+    pub is_user_exception: bool,
+    /// Type of exception. If it's a user exception, the type is passed along to app insights.
+    /// Otherwise, it's ignored for now.
+    #[prost(string, tag="5")]
+    pub r#type: ::prost::alloc::string::String,
 }
 /// Http cookie type. Note that only name and value are used for Http requests
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -574,6 +834,81 @@ pub struct RpcHttp {
     pub identities: ::prost::alloc::vec::Vec<RpcClaimsIdentity>,
     #[prost(message, repeated, tag="19")]
     pub cookies: ::prost::alloc::vec::Vec<RpcHttpCookie>,
+    #[prost(map="string, message", tag="20")]
+    pub nullable_headers: ::std::collections::HashMap<::prost::alloc::string::String, NullableString>,
+    #[prost(map="string, message", tag="21")]
+    pub nullable_params: ::std::collections::HashMap<::prost::alloc::string::String, NullableString>,
+    #[prost(map="string, message", tag="22")]
+    pub nullable_query: ::std::collections::HashMap<::prost::alloc::string::String, NullableString>,
+}
+/// Message representing Microsoft.Azure.WebJobs.ParameterBindingData
+/// Used for hydrating SDK-type bindings in out-of-proc workers
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ModelBindingData {
+    /// The version of the binding data content
+    #[prost(string, tag="1")]
+    pub version: ::prost::alloc::string::String,
+    /// The extension source of the binding data
+    #[prost(string, tag="2")]
+    pub source: ::prost::alloc::string::String,
+    /// The content type of the binding data content
+    #[prost(string, tag="3")]
+    pub content_type: ::prost::alloc::string::String,
+    /// The binding data content
+    #[prost(bytes="vec", tag="4")]
+    pub content: ::prost::alloc::vec::Vec<u8>,
+}
+/// Used to encapsulate collection model_binding_data
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionModelBindingData {
+    #[prost(message, repeated, tag="1")]
+    pub model_binding_data: ::prost::alloc::vec::Vec<ModelBindingData>,
+}
+/// Retry policy which the worker sends the host when the worker indexes
+/// a function.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RpcRetryOptions {
+    /// The first retry waits for the minimum delay.
+    #[prost(int32, tag="2")]
+    pub max_retry_count: i32,
+    /// Delay between retries
+    #[prost(message, optional, tag="3")]
+    pub delay_interval: ::core::option::Option<::prost_types::Duration>,
+    /// Minimum retry delay
+    #[prost(message, optional, tag="4")]
+    pub minimum_interval: ::core::option::Option<::prost_types::Duration>,
+    /// Maximum retry delay
+    #[prost(message, optional, tag="5")]
+    pub maximum_interval: ::core::option::Option<::prost_types::Duration>,
+    #[prost(enumeration="rpc_retry_options::RetryStrategy", tag="6")]
+    pub retry_strategy: i32,
+}
+/// Nested message and enum types in `RpcRetryOptions`.
+pub mod rpc_retry_options {
+    /// The retry strategy to use. Valid values are fixed delay or exponential backoff.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum RetryStrategy {
+        ExponentialBackoff = 0,
+        FixedDelay = 1,
+    }
+}
+/// Used to specify the type of data contained in shared memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RpcDataType {
+    Unknown = 0,
+    String = 1,
+    Json = 2,
+    Bytes = 3,
+    Stream = 4,
+    Http = 5,
+    Int = 6,
+    Double = 7,
+    CollectionBytes = 8,
+    CollectionString = 9,
+    CollectionDouble = 10,
+    CollectionSint64 = 11,
 }
 /// Generated client implementations.
 pub mod function_rpc_client {
@@ -664,3 +999,131 @@ pub mod function_rpc_client {
         }
     }
 }
+/// Generated server implementations.
+pub mod function_rpc_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with FunctionRpcServer.
+    #[async_trait]
+    pub trait FunctionRpc: Send + Sync + 'static {
+        /// Server streaming response type for the EventStream method.
+        type EventStreamStream: futures_core::Stream<Item = Result<super::StreamingMessage, tonic::Status>>
+            + Send
+            + 'static;
+        async fn event_stream(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::StreamingMessage>>,
+        ) -> Result<tonic::Response<Self::EventStreamStream>, tonic::Status>;
+    }
+    /// Interface exported by the server.
+    #[derive(Debug)]
+    pub struct FunctionRpcServer<T: FunctionRpc> {
+        inner: _Inner<T>,
+        accept_compression_encodings: (),
+        send_compression_encodings: (),
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: FunctionRpc> FunctionRpcServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+            }
+        }
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for FunctionRpcServer<T>
+    where
+        T: FunctionRpc,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/AzureFunctionsRpcMessages.FunctionRpc/EventStream" => {
+                    #[allow(non_camel_case_types)]
+                    struct EventStreamSvc<T: FunctionRpc>(pub Arc<T>);
+                    impl<T: FunctionRpc> tonic::server::StreamingService<super::StreamingMessage>
+                        for EventStreamSvc<T>
+                    {
+                        type Response = super::StreamingMessage;
+                        type ResponseStream = T::EventStreamStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<tonic::Streaming<super::StreamingMessage>>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).event_stream(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = EventStreamSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => Box::pin(async move {
+                    Ok(http::Response::builder()
+                        .status(200)
+                        .header("grpc-status", "12")
+                        .header("content-type", "application/grpc")
+                        .body(empty_body())
+                        .unwrap())
+                }),
+            }
+        }
+    }
+    impl<T: FunctionRpc> Clone for FunctionRpcServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+            }
+        }
+    }
+    impl<T: FunctionRpc> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: FunctionRpc> tonic::transport::NamedService for FunctionRpcServer<T> {
+        const NAME: &'static str = "AzureFunctionsRpcMessages.FunctionRpc";
+    }
+}
@@ -1,7 +1,7 @@
 use crate::codegen::{
     bindings::Binding,
-    get_boolean_value, get_string_value, iter_attribute_args, macro_panic,
-    quotable::{QuotableBorrowedStr, QuotableOption},
+    get_boolean_value, get_integer_value, get_string_value, iter_attribute_args, macro_panic,
+    quotable::{QuotableBorrowedStr, QuotableDuration, QuotableOption, QuotableStrArray},
 };
 use crate::rpc;
 use proc_macro2::{Span, TokenStream};
@@ -10,6 +10,7 @@ use serde::{ser::SerializeMap, Serialize, Serializer};
 use std::borrow::Cow;
 use std::future::Future;
 use std::pin::Pin;
+use std::time::Duration;
 use syn::{parse_str, spanned::Spanned, AttributeArgs, Ident};
 
 pub type InvocationFuture = Pin<Box<dyn Future<Output = rpc::InvocationResponse> + Send>>;
@@ -68,6 +69,9 @@ pub struct Function {
     pub disabled: bool,
     pub bindings: Cow<'static, [Binding]>,
     pub invoker: Option<Invoker>,
+    pub middleware: Cow<'static, [Cow<'static, str>]>,
+    pub max_concurrency: Option<usize>,
+    pub timeout: Option<Duration>,
     pub manifest_dir: Option<Cow<'static, str>>,
     pub file: Option<Cow<'static, str>>,
 }
@@ -93,6 +97,9 @@ impl From<AttributeArgs> for Function {
     fn from(args: AttributeArgs) -> Self {
         let mut name = None;
         let mut disabled = None;
+        let mut middleware = Vec::new();
+        let mut max_concurrency = None;
+        let mut timeout = None;
 
         iter_attribute_args(&args, |key, value| {
             let key_name = key.to_string();
@@ -113,6 +120,38 @@ impl From<AttributeArgs> for Function {
                     }
                 }
                 "disabled" => disabled = Some(get_boolean_value("disabled", value)),
+                "middleware" => {
+                    for name in get_string_value("middleware", value).split('|') {
+                        let name = name.trim();
+                        if name.is_empty() {
+                            macro_panic(
+                                value.span(),
+                                "middleware names cannot be empty; separate names with '|'",
+                            );
+                        }
+                        middleware.push(Cow::from(name.to_owned()));
+                    }
+                }
+                "max_concurrency" => {
+                    let limit = get_integer_value("max_concurrency", value);
+                    if limit <= 0 {
+                        macro_panic(
+                            value.span(),
+                            "the 'max_concurrency' argument must be greater than zero",
+                        );
+                    }
+                    max_concurrency = Some(limit as usize);
+                }
+                "timeout" => {
+                    timeout = Some(
+                        parse_timeout(&get_string_value("timeout", value)).unwrap_or_else(|| {
+                            macro_panic(
+                                value.span(),
+                                "the 'timeout' argument must be a non-zero duration in the format 'hh:mm:ss'",
+                            )
+                        }),
+                    );
+                }
                 _ => macro_panic(
                     key.span(),
                     format!("unsupported attribue argument '{}'", key_name),
@@ -127,18 +166,44 @@ impl From<AttributeArgs> for Function {
             disabled: disabled.unwrap_or(false),
             bindings: Cow::Owned(Vec::new()),
             invoker: None,
+            middleware: Cow::Owned(middleware),
+            max_concurrency,
+            timeout,
             manifest_dir: None,
             file: None,
         }
     }
 }
 
+// Parses a timeout in the 'hh:mm:ss' format used by the Azure Functions Host
+fn parse_timeout(s: &str) -> Option<Duration> {
+    let parts = s
+        .split(':')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match parts.as_slice() {
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => {
+            let secs = hours * 3600 + minutes * 60 + seconds;
+            if secs == 0 {
+                None
+            } else {
+                Some(Duration::from_secs(secs))
+            }
+        }
+        _ => None,
+    }
+}
+
 impl ToTokens for Function {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let name = QuotableBorrowedStr(&self.name);
         let disabled = self.disabled;
         let bindings = self.bindings.iter();
         let invoker = QuotableOption(self.invoker.as_ref());
+        let middleware = QuotableStrArray(&self.middleware);
+        let max_concurrency = QuotableOption(self.max_concurrency);
+        let timeout = QuotableOption(self.timeout.map(QuotableDuration));
 
         quote!(
             ::azure_functions::codegen::Function {
@@ -146,6 +211,9 @@ impl ToTokens for Function {
                 disabled: #disabled,
                 bindings: ::std::borrow::Cow::Borrowed(&[#(#bindings),*]),
                 invoker: #invoker,
+                middleware: #middleware,
+                max_concurrency: #max_concurrency,
+                timeout: #timeout,
                 manifest_dir: Some(::std::borrow::Cow::Borrowed(env!("CARGO_MANIFEST_DIR"))),
                 file: Some(::std::borrow::Cow::Borrowed(file!())),
             }
@@ -186,6 +254,9 @@ mod tests {
                 name: Cow::Borrowed("invoker"),
                 invoker_fn: InvokerFn::Async(None),
             }),
+            middleware: Cow::Owned(vec![Cow::from("auth")]),
+            max_concurrency: Some(4),
+            timeout: Some(Duration::from_secs(90)),
             manifest_dir: None,
             file: None,
         };
@@ -201,6 +272,9 @@ mod tests {
         let func: Function = vec![
             parse_str::<NestedMeta>(r#"name = "foo""#).unwrap(),
             parse_str::<NestedMeta>(r#"disabled = true"#).unwrap(),
+            parse_str::<NestedMeta>(r#"middleware = "auth | timing""#).unwrap(),
+            parse_str::<NestedMeta>(r#"max_concurrency = 4"#).unwrap(),
+            parse_str::<NestedMeta>(r#"timeout = "01:01:30""#).unwrap(),
         ]
         .into();
 
@@ -208,6 +282,11 @@ mod tests {
         assert_eq!(func.disabled, true);
         assert_eq!(func.bindings.len(), 0);
         assert_eq!(func.invoker.is_none(), true);
+        assert_eq!(func.middleware.len(), 2);
+        assert_eq!(func.middleware[0], "auth");
+        assert_eq!(func.middleware[1], "timing");
+        assert_eq!(func.max_concurrency, Some(4));
+        assert_eq!(func.timeout, Some(Duration::from_secs(3690)));
         assert_eq!(func.manifest_dir.is_none(), true);
         assert_eq!(func.file.is_none(), true);
     }
@@ -232,6 +311,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_requires_nonempty_middleware_names() {
+        should_panic(
+            || {
+                let _: Function =
+                    vec![parse_str::<NestedMeta>(r#"middleware = "auth||timing""#).unwrap()].into();
+            },
+            "middleware names cannot be empty; separate names with '|'",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_positive_max_concurrency() {
+        should_panic(
+            || {
+                let _: Function =
+                    vec![parse_str::<NestedMeta>(r#"max_concurrency = 0"#).unwrap()].into();
+            },
+            "the 'max_concurrency' argument must be greater than zero",
+        );
+    }
+
+    #[test]
+    fn it_requires_a_valid_timeout() {
+        for timeout in &["00:00:00", "00:60:00", "30", "1:2:3:4", "00:00:1.5"] {
+            should_panic(
+                || {
+                    let _: Function =
+                        vec![
+                            parse_str::<NestedMeta>(&format!(r#"timeout = "{}""#, timeout))
+                                .unwrap(),
+                        ]
+                        .into();
+                },
+                "the 'timeout' argument must be a non-zero duration in the format 'hh:mm:ss'",
+            );
+        }
+    }
+
     #[test]
     fn it_requires_the_disabled_attribute_be_a_boolean() {
         should_panic(
@@ -263,6 +381,9 @@ mod tests {
                 name: Cow::Borrowed("invoker"),
                 invoker_fn: InvokerFn::Async(None),
             }),
+            middleware: Cow::Owned(vec![Cow::from("auth")]),
+            max_concurrency: Some(4),
+            timeout: Some(Duration::from_secs(90)),
             manifest_dir: None,
             file: None,
         };
@@ -274,7 +395,7 @@ mod tests {
 
         assert_eq!(
             tokens,
-            r#"::azure_functions::codegen::Function{name:::std::borrow::Cow::Borrowed("name"),disabled:false,bindings:::std::borrow::Cow::Borrowed(&[::azure_functions::codegen::bindings::Binding::HttpTrigger(::azure_functions::codegen::bindings::HttpTrigger{name:::std::borrow::Cow::Borrowed("foo"),auth_level:Some(::std::borrow::Cow::Borrowed("bar")),methods:::std::borrow::Cow::Borrowed(&[::std::borrow::Cow::Borrowed("foo"),::std::borrow::Cow::Borrowed("bar"),::std::borrow::Cow::Borrowed("baz"),]),route:Some(::std::borrow::Cow::Borrowed("baz")),}),::azure_functions::codegen::bindings::Binding::Http(::azure_functions::codegen::bindings::Http{name:::std::borrow::Cow::Borrowed("bar"),})]),invoker:Some(::azure_functions::codegen::Invoker{name:::std::borrow::Cow::Borrowed("invoker"),invoker_fn:::azure_functions::codegen::InvokerFn::Async(Some(invoker)),}),manifest_dir:Some(::std::borrow::Cow::Borrowed(env!("CARGO_MANIFEST_DIR"))),file:Some(::std::borrow::Cow::Borrowed(file!())),}"#
+            r#"::azure_functions::codegen::Function{name:::std::borrow::Cow::Borrowed("name"),disabled:false,bindings:::std::borrow::Cow::Borrowed(&[::azure_functions::codegen::bindings::Binding::HttpTrigger(::azure_functions::codegen::bindings::HttpTrigger{name:::std::borrow::Cow::Borrowed("foo"),auth_level:Some(::std::borrow::Cow::Borrowed("bar")),methods:::std::borrow::Cow::Borrowed(&[::std::borrow::Cow::Borrowed("foo"),::std::borrow::Cow::Borrowed("bar"),::std::borrow::Cow::Borrowed("baz"),]),route:Some(::std::borrow::Cow::Borrowed("baz")),}),::azure_functions::codegen::bindings::Binding::Http(::azure_functions::codegen::bindings::Http{name:::std::borrow::Cow::Borrowed("bar"),})]),invoker:Some(::azure_functions::codegen::Invoker{name:::std::borrow::Cow::Borrowed("invoker"),invoker_fn:::azure_functions::codegen::InvokerFn::Async(Some(invoker)),}),middleware:::std::borrow::Cow::Borrowed(&[::std::borrow::Cow::Borrowed("auth"),]),max_concurrency:Some(4usize),timeout:Some(::std::time::Duration::from_secs(90u64)),manifest_dir:Some(::std::borrow::Cow::Borrowed(env!("CARGO_MANIFEST_DIR"))),file:Some(::std::borrow::Cow::Borrowed(file!())),}"#
         );
     }
 }
@@ -2,6 +2,7 @@ use crate::codegen::bindings::Direction;
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use std::borrow::Cow;
+use std::time::Duration;
 
 pub struct QuotableBorrowedStr<'a>(pub &'a str);
 
@@ -33,6 +34,15 @@ impl<T: ToTokens> ToTokens for QuotableOption<T> {
     }
 }
 
+pub struct QuotableDuration(pub Duration);
+
+impl ToTokens for QuotableDuration {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let secs = self.0.as_secs();
+        quote!(::std::time::Duration::from_secs(#secs)).to_tokens(tokens);
+    }
+}
+
 pub struct QuotableDirection(pub Direction);
 
 impl ToTokens for QuotableDirection {
@@ -16,4 +16,34 @@ pub mod util;
 #[allow(clippy::type_repetition_in_bounds)]
 pub mod rpc {
     tonic::include_proto!("azure_functions_rpc_messages");
+
+    impl ParameterBinding {
+        /// Creates a parameter binding with the given name and data.
+        pub fn new<S: Into<String>>(name: S, data: TypedData) -> Self {
+            ParameterBinding {
+                name: name.into(),
+                rpc_data: Some(parameter_binding::RpcData::Data(data)),
+            }
+        }
+
+        /// Gets the data of the binding.
+        ///
+        /// Returns None if the binding has no data or its data is in shared memory.
+        pub fn data(&self) -> Option<&TypedData> {
+            match &self.rpc_data {
+                Some(parameter_binding::RpcData::Data(data)) => Some(data),
+                _ => None,
+            }
+        }
+
+        /// Converts the binding into its data.
+        ///
+        /// Returns None if the binding has no data or its data is in shared memory.
+        pub fn into_data(self) -> Option<TypedData> {
+            match self.rpc_data {
+                Some(parameter_binding::RpcData::Data(data)) => Some(data),
+                _ => None,
+            }
+        }
+    }
 }
@@ -9,6 +9,11 @@ use std::borrow::Cow;
 use std::fmt;
 use std::str::from_utf8;
 
+#[cfg(feature = "testing")]
+use crate::testing::BindingBuilder;
+#[cfg(feature = "testing")]
+use std::collections::HashMap;
+
 /// Represents an Azure Storage blob input or output binding.
 ///
 /// The following binding attributes are supported:
@@ -77,6 +82,24 @@ impl Blob {
         }
     }
 
+    /// Creates a builder for a blob input binding.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::Blob;
+    ///
+    /// let blob = Blob::builder().content("hello world").build();
+    ///
+    /// assert_eq!(blob.as_str().unwrap(), "hello world");
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> BlobBuilder {
+        BlobBuilder::new()
+    }
+
     /// Gets the content of the blob as a slice of bytes.
     pub fn as_bytes(&self) -> &[u8] {
         match &self.0.data {
@@ -218,6 +241,46 @@ impl Into<TypedData> for Blob {
     }
 }
 
+/// Represents a builder for blob input bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct BlobBuilder(Blob);
+
+#[cfg(feature = "testing")]
+impl BlobBuilder {
+    /// Creates a new `BlobBuilder` for an empty blob.
+    pub fn new() -> BlobBuilder {
+        BlobBuilder(Blob::from(""))
+    }
+
+    /// Sets the content of the blob.
+    pub fn content<T: Into<Blob>>(mut self, content: T) -> Self {
+        self.0 = content.into();
+        self
+    }
+
+    /// Consumes the builder and returns the blob.
+    pub fn build(self) -> Blob {
+        self.0
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for BlobBuilder {
+    fn default() -> Self {
+        BlobBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for BlobBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (self.0.into(), HashMap::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
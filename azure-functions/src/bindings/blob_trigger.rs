@@ -7,6 +7,14 @@ use crate::{
 use serde_json::from_str;
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::{
+    testing::BindingBuilder,
+    util::{json_data, string_data},
+};
+#[cfg(feature = "testing")]
+use serde_json::{json, Map, Value};
+
 const PATH_KEY: &str = "BlobTrigger";
 const URI_KEY: &str = "Uri";
 const PROPERTIES_KEY: &str = "Properties";
@@ -84,6 +92,151 @@ impl BlobTrigger {
                 }),
         }
     }
+
+    /// Creates a builder for a blob trigger.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::BlobTrigger;
+    ///
+    /// let trigger = BlobTrigger::builder()
+    ///     .blob("hello world")
+    ///     .path("example/hello.txt")
+    ///     .metadata("author", "Peter")
+    ///     .build();
+    ///
+    /// assert_eq!(trigger.blob.as_str().unwrap(), "hello world");
+    /// assert_eq!(trigger.path, "example/hello.txt");
+    /// assert_eq!(trigger.metadata.get("author").unwrap(), "Peter");
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> BlobTriggerBuilder {
+        BlobTriggerBuilder::new()
+    }
+}
+
+/// Represents a builder for blob trigger bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct BlobTriggerBuilder {
+    data: TypedData,
+    metadata: HashMap<String, TypedData>,
+    properties: Map<String, Value>,
+    blob_metadata: HashMap<String, String>,
+}
+
+#[cfg(feature = "testing")]
+impl BlobTriggerBuilder {
+    /// Creates a new `BlobTriggerBuilder` for an empty, unleased block blob.
+    pub fn new() -> BlobTriggerBuilder {
+        let mut metadata = HashMap::new();
+        metadata.insert(PATH_KEY.to_owned(), string_data(""));
+
+        let properties = match json!({
+            "AppendBlobCommittedBlockCount": null,
+            "BlobTierInferred": null,
+            "BlobTierLastModifiedTime": null,
+            "BlobType": 2,
+            "CacheControl": null,
+            "ContentDisposition": null,
+            "ContentEncoding": null,
+            "ContentLanguage": null,
+            "ContentMD5": null,
+            "ContentType": null,
+            "Created": null,
+            "DeletedTime": null,
+            "ETag": null,
+            "IsIncrementalCopy": false,
+            "IsServerEncrypted": false,
+            "LastModified": null,
+            "LeaseDuration": 0,
+            "LeaseState": 1,
+            "LeaseStatus": 2,
+            "Length": 0,
+            "PageBlobSequenceNumber": null,
+            "PremiumPageBlobTier": null,
+            "RehydrationStatus": null,
+            "RemainingDaysBeforePermanentDelete": null,
+            "StandardBlobTier": null,
+        }) {
+            Value::Object(properties) => properties,
+            _ => unreachable!(),
+        };
+
+        BlobTriggerBuilder {
+            data: Blob::from("").into(),
+            metadata,
+            properties,
+            blob_metadata: HashMap::new(),
+        }
+    }
+
+    /// Sets the contents of the blob that triggered the function.
+    ///
+    /// This also sets the `Length` property of the blob.
+    pub fn blob<T: Into<Blob>>(mut self, blob: T) -> Self {
+        let blob = blob.into();
+        self.properties
+            .insert("Length".to_owned(), blob.as_bytes().len().into());
+        self.data = blob.into();
+        self
+    }
+
+    /// Sets the path of the blob.
+    pub fn path<T: Into<String>>(mut self, path: T) -> Self {
+        self.metadata.insert(PATH_KEY.to_owned(), string_data(path));
+        self
+    }
+
+    /// Sets the URI of the blob.
+    pub fn uri<T: Into<String>>(mut self, uri: T) -> Self {
+        self.metadata.insert(URI_KEY.to_owned(), string_data(uri));
+        self
+    }
+
+    /// Sets a property of the blob.
+    ///
+    /// The property is named and valued as it is sent by the Azure Functions Host
+    /// (e.g. `property("ContentType", json!("text/plain"))`).
+    pub fn property<T: Into<String>>(mut self, name: T, value: Value) -> Self {
+        self.properties.insert(name.into(), value);
+        self
+    }
+
+    /// Sets a user-defined metadata value of the blob.
+    pub fn metadata<T: Into<String>, U: Into<String>>(mut self, name: T, value: U) -> Self {
+        self.blob_metadata.insert(name.into(), value.into());
+        self
+    }
+
+    /// Consumes the builder and returns the blob trigger.
+    pub fn build(self) -> BlobTrigger {
+        let (data, metadata) = self.into_data();
+        BlobTrigger::new(data, metadata)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for BlobTriggerBuilder {
+    fn default() -> Self {
+        BlobTriggerBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for BlobTriggerBuilder {
+    fn into_data(mut self) -> (TypedData, HashMap<String, TypedData>) {
+        self.metadata
+            .insert(PROPERTIES_KEY.to_owned(), json_data(&self.properties));
+        self.metadata
+            .insert(METADATA_KEY.to_owned(), json_data(&self.blob_metadata));
+        (self.data, self.metadata)
+    }
 }
 
 #[cfg(test)]
@@ -259,4 +412,24 @@ mod tests {
             USER_METADATA_VALUE
         );
     }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn it_builds_a_trigger() {
+        let trigger = BlobTrigger::builder()
+            .blob("hello world")
+            .path("example/hello.txt")
+            .property("ContentType", json!("text/plain"))
+            .metadata("key", "value")
+            .build();
+
+        assert_eq!(trigger.blob.as_str().unwrap(), "hello world");
+        assert_eq!(trigger.path, "example/hello.txt");
+        assert_eq!(trigger.properties.length, 11);
+        assert_eq!(
+            trigger.properties.content_type.as_ref().unwrap(),
+            "text/plain"
+        );
+        assert_eq!(trigger.metadata.get("key").unwrap(), "value");
+    }
 }
@@ -8,6 +8,11 @@ use serde_json::{from_str, Map, Value};
 use std::borrow::Cow;
 use std::fmt;
 
+#[cfg(feature = "testing")]
+use crate::{testing::BindingBuilder, util::json_data};
+#[cfg(feature = "testing")]
+use std::collections::HashMap;
+
 /// Represents the input or output binding for a Cosmos DB document.
 ///
 /// The following binding attributes are supported:
@@ -110,6 +115,27 @@ impl CosmosDbDocument {
         CosmosDbDocument(value)
     }
 
+    /// Creates a builder for a Cosmos DB document input binding.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::CosmosDbDocument;
+    /// use serde_json::json;
+    ///
+    /// let document = CosmosDbDocument::builder()
+    ///     .document(json!({ "id": "1", "name": "Peter" }))
+    ///     .build();
+    ///
+    /// assert_eq!(document.as_object().unwrap()["name"], "Peter");
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> CosmosDbDocumentBuilder {
+        CosmosDbDocumentBuilder::new()
+    }
+
     /// Gets whether or not the Cosmos DB document is null.
     ///
     /// A Cosmos DB document can be null as a result of a query that returned no documents.
@@ -242,6 +268,45 @@ impl Into<TypedData> for CosmosDbDocument {
     }
 }
 
+/// Represents a builder for Cosmos DB document input bindings.
+///
+/// The builder produces the documents returned by the query of the binding; building
+/// a single document returns the first document or a null document if there are none.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug, Default)]
+pub struct CosmosDbDocumentBuilder {
+    documents: Vec<Value>,
+}
+
+#[cfg(feature = "testing")]
+impl CosmosDbDocumentBuilder {
+    /// Creates a new `CosmosDbDocumentBuilder` with no documents.
+    pub fn new() -> CosmosDbDocumentBuilder {
+        CosmosDbDocumentBuilder::default()
+    }
+
+    /// Adds a document returned by the query.
+    pub fn document(mut self, document: Value) -> Self {
+        self.documents.push(document);
+        self
+    }
+
+    /// Consumes the builder and returns the Cosmos DB document.
+    pub fn build(self) -> CosmosDbDocument {
+        let (data, _) = self.into_data();
+        data.into()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for CosmosDbDocumentBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (json_data(&self.documents), HashMap::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,6 +2,9 @@ use crate::{rpc::TypedData, util::convert_from};
 use serde_json::Value;
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::{testing::BindingBuilder, util::json_data};
+
 /// Represents a Cosmos DB trigger binding.
 ///
 /// The following binding attributes are supported:
@@ -64,6 +67,64 @@ impl CosmosDbTrigger {
             _ => panic!("expected a JSON array for Cosmos DB trigger data"),
         }
     }
+
+    /// Creates a builder for a Cosmos DB trigger.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::CosmosDbTrigger;
+    /// use serde_json::json;
+    ///
+    /// let trigger = CosmosDbTrigger::builder()
+    ///     .document(json!({ "id": "1", "name": "Peter" }))
+    ///     .build();
+    ///
+    /// assert_eq!(trigger.documents.len(), 1);
+    /// assert_eq!(trigger.documents[0]["name"], "Peter");
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> CosmosDbTriggerBuilder {
+        CosmosDbTriggerBuilder::new()
+    }
+}
+
+/// Represents a builder for Cosmos DB trigger bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug, Default)]
+pub struct CosmosDbTriggerBuilder {
+    documents: Vec<Value>,
+}
+
+#[cfg(feature = "testing")]
+impl CosmosDbTriggerBuilder {
+    /// Creates a new `CosmosDbTriggerBuilder` with no documents.
+    pub fn new() -> CosmosDbTriggerBuilder {
+        CosmosDbTriggerBuilder::default()
+    }
+
+    /// Adds a document that was created or modified.
+    pub fn document(mut self, document: Value) -> Self {
+        self.documents.push(document);
+        self
+    }
+
+    /// Consumes the builder and returns the Cosmos DB trigger.
+    pub fn build(self) -> CosmosDbTrigger {
+        let (data, metadata) = self.into_data();
+        CosmosDbTrigger::new(data, metadata)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for CosmosDbTriggerBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (json_data(&self.documents), HashMap::new())
+    }
 }
 
 #[cfg(test)]
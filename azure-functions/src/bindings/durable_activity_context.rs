@@ -3,6 +3,12 @@ use serde::Deserialize;
 use serde_json::{from_str, Number, Value};
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::{
+    testing::BindingBuilder,
+    util::{json_data, string_data},
+};
+
 const INSTANCE_ID_KEY: &str = "instanceId";
 
 /// Represents the Durable Functions activity context binding.
@@ -59,6 +65,82 @@ impl DurableActivityContext {
                 .expect("expected an instance id"),
         }
     }
+
+    /// Creates a builder for a Durable Functions activity context.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::DurableActivityContext;
+    /// use serde_json::json;
+    ///
+    /// let context = DurableActivityContext::builder()
+    ///     .input(json!("Peter"))
+    ///     .instance_id("1234")
+    ///     .build();
+    ///
+    /// assert_eq!(context.input, json!("Peter"));
+    /// assert_eq!(context.instance_id, "1234");
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> DurableActivityContextBuilder {
+        DurableActivityContextBuilder::new()
+    }
+}
+
+/// Represents a builder for Durable Functions activity contexts.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct DurableActivityContextBuilder(DurableActivityContext);
+
+#[cfg(feature = "testing")]
+impl DurableActivityContextBuilder {
+    /// Creates a new `DurableActivityContextBuilder` with a null input.
+    pub fn new() -> DurableActivityContextBuilder {
+        DurableActivityContextBuilder(DurableActivityContext {
+            input: Value::Null,
+            instance_id: String::new(),
+        })
+    }
+
+    /// Sets the input to the activity function.
+    pub fn input(mut self, input: Value) -> Self {
+        self.0.input = input;
+        self
+    }
+
+    /// Sets the orchestration instance identifier.
+    pub fn instance_id<T: Into<String>>(mut self, id: T) -> Self {
+        self.0.instance_id = id.into();
+        self
+    }
+
+    /// Consumes the builder and returns the activity context.
+    pub fn build(self) -> DurableActivityContext {
+        let (data, metadata) = self.into_data();
+        DurableActivityContext::new(data, metadata)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for DurableActivityContextBuilder {
+    fn default() -> Self {
+        DurableActivityContextBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for DurableActivityContextBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        let mut metadata = HashMap::new();
+        metadata.insert(INSTANCE_ID_KEY.to_owned(), string_data(self.0.instance_id));
+
+        (json_data(&self.0.input), metadata)
+    }
 }
 
 #[cfg(test)]
@@ -7,6 +7,13 @@ use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::{from_str, to_value, Value};
 
+#[cfg(feature = "testing")]
+use crate::{testing::BindingBuilder, util::string_data};
+#[cfg(feature = "testing")]
+use serde_json::json;
+#[cfg(feature = "testing")]
+use std::collections::HashMap;
+
 /// Represents the Durable Functions orchestration client input binding.
 ///
 /// The following binding attributes are supported:
@@ -185,6 +192,84 @@ impl<'a> Into<Body<'a>> for OrchestrationData {
     }
 }
 
+#[cfg(feature = "testing")]
+impl DurableOrchestrationClient {
+    /// Creates a builder for a Durable Functions orchestration client.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::DurableOrchestrationClient;
+    ///
+    /// let client = DurableOrchestrationClient::builder()
+    ///     .status_query_url("http://localhost:8080/runtime/webhooks/durabletask/instances/INSTANCEID?taskHub=ExampleHub&connection=Storage&code=secret")
+    ///     .build();
+    /// ```
+    pub fn builder() -> DurableOrchestrationClientBuilder {
+        DurableOrchestrationClientBuilder::new()
+    }
+}
+
+/// Represents a builder for Durable Functions orchestration clients.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct DurableOrchestrationClientBuilder {
+    status_query_url: String,
+}
+
+#[cfg(feature = "testing")]
+impl DurableOrchestrationClientBuilder {
+    /// Creates a new `DurableOrchestrationClientBuilder` for the `DurableFunctionsHub` task hub
+    /// of a local Azure Functions Host.
+    pub fn new() -> DurableOrchestrationClientBuilder {
+        DurableOrchestrationClientBuilder {
+            status_query_url: "http://localhost:7071/runtime/webhooks/durabletask/instances/INSTANCEID?taskHub=DurableFunctionsHub&connection=Storage&code=".to_owned(),
+        }
+    }
+
+    /// Sets the status query URL that the Durable Functions extension provides to the client.
+    ///
+    /// The client's requests are sent to the endpoint of this URL.
+    pub fn status_query_url<T: Into<String>>(mut self, url: T) -> Self {
+        self.status_query_url = url.into();
+        self
+    }
+
+    /// Consumes the builder and returns the orchestration client.
+    pub fn build(self) -> DurableOrchestrationClient {
+        let (data, _) = self.into_data();
+        data.into()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for DurableOrchestrationClientBuilder {
+    fn default() -> Self {
+        DurableOrchestrationClientBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for DurableOrchestrationClientBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (
+            string_data(
+                json!({
+                    "managementUrls": {
+                        "statusQueryGetUri": self.status_query_url,
+                    }
+                })
+                .to_string(),
+            ),
+            HashMap::new(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,4 +283,11 @@ mod tests {
         let client: DurableOrchestrationClient = data.into();
         assert_eq!(client.client.task_hub(), "DurableFunctionsHub");
     }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn it_builds_a_client_for_a_local_host() {
+        let client = DurableOrchestrationClient::builder().build();
+        assert_eq!(client.client.task_hub(), "DurableFunctionsHub");
+    }
 }
@@ -10,6 +10,11 @@ use serde::Deserialize;
 use serde_json::{from_str, Value};
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
+#[cfg(feature = "testing")]
+use crate::{testing::BindingBuilder, util::string_data};
+#[cfg(feature = "testing")]
+use serde_json::json;
+
 /// Represents the Durable Functions orchestration context binding.
 ///
 /// The following binding attributes are supported:
@@ -94,6 +99,30 @@ impl DurableOrchestrationContext {
         }
     }
 
+    /// Creates a builder for a Durable Functions orchestration context.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::DurableOrchestrationContext;
+    /// use serde_json::json;
+    ///
+    /// let context = DurableOrchestrationContext::builder()
+    ///     .instance_id("1234")
+    ///     .input(json!("Peter"))
+    ///     .build();
+    ///
+    /// assert_eq!(context.instance_id, "1234");
+    /// assert_eq!(context.input, json!("Peter"));
+    /// assert!(!context.is_replaying());
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> DurableOrchestrationContextBuilder {
+        DurableOrchestrationContextBuilder::new()
+    }
+
     /// Gets a value indicating whether the orchestrator function is currently replaying itself.
     pub fn is_replaying(&self) -> bool {
         self.state.borrow().is_replaying()
@@ -360,6 +389,110 @@ impl DurableOrchestrationContext {
     }
 }
 
+/// Represents a builder for Durable Functions orchestration contexts.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct DurableOrchestrationContextBuilder {
+    instance_id: String,
+    parent_instance_id: Option<String>,
+    input: Value,
+    history: Option<Vec<Value>>,
+}
+
+#[cfg(feature = "testing")]
+impl DurableOrchestrationContextBuilder {
+    /// Creates a new `DurableOrchestrationContextBuilder` for a new orchestration
+    /// started now with a null input.
+    pub fn new() -> DurableOrchestrationContextBuilder {
+        DurableOrchestrationContextBuilder {
+            instance_id: String::new(),
+            parent_instance_id: None,
+            input: Value::Null,
+            history: None,
+        }
+    }
+
+    /// Sets the orchestration instance identifier.
+    pub fn instance_id<T: Into<String>>(mut self, id: T) -> Self {
+        self.instance_id = id.into();
+        self
+    }
+
+    /// Sets the parent orchestration instance identifier.
+    pub fn parent_instance_id<T: Into<String>>(mut self, id: T) -> Self {
+        self.parent_instance_id = Some(id.into());
+        self
+    }
+
+    /// Sets the input value to the orchestration.
+    pub fn input(mut self, input: Value) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Sets the history of the orchestration to replay.
+    ///
+    /// The history events are given as the JSON objects sent by the Durable Functions extension
+    /// and must contain an `OrchestratorStarted` event.
+    pub fn history(mut self, history: Vec<Value>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Consumes the builder and returns the orchestration context.
+    pub fn build(self) -> DurableOrchestrationContext {
+        let (data, metadata) = self.into_data();
+        DurableOrchestrationContext::new(data, metadata)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for DurableOrchestrationContextBuilder {
+    fn default() -> Self {
+        DurableOrchestrationContextBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for DurableOrchestrationContextBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        let input = self.input;
+        let history = self.history.unwrap_or_else(|| {
+            let now = Utc::now().to_rfc3339();
+            vec![
+                json!({
+                    "EventType": EventType::OrchestratorStarted as u8,
+                    "EventId": -1,
+                    "IsPlayed": false,
+                    "Timestamp": now,
+                }),
+                json!({
+                    "EventType": EventType::ExecutionStarted as u8,
+                    "EventId": -1,
+                    "IsPlayed": false,
+                    "Timestamp": now,
+                    "Input": input,
+                }),
+            ]
+        });
+
+        (
+            string_data(
+                json!({
+                    "instanceId": self.instance_id,
+                    "parentInstanceId": self.parent_instance_id,
+                    "input": input,
+                    "history": history,
+                })
+                .to_string(),
+            ),
+            HashMap::new(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,6 +7,11 @@ use serde::Deserialize;
 use serde_json::from_str;
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::{testing::BindingBuilder, util::json_data};
+#[cfg(feature = "testing")]
+use serde_json::json;
+
 /// Represents an Event Grid trigger binding.
 ///
 /// The following binding attributes are supported:
@@ -59,6 +64,129 @@ impl EventGridEvent {
             _ => panic!("expected JSON data for Event Grid trigger binding"),
         }
     }
+
+    /// Creates a builder for an Event Grid event.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::EventGridEvent;
+    /// use serde_json::json;
+    ///
+    /// let event = EventGridEvent::builder()
+    ///     .subject("/example/1")
+    ///     .event_type("Example.Created")
+    ///     .data(json!({ "name": "Peter" }))
+    ///     .build();
+    ///
+    /// assert_eq!(event.subject, "/example/1");
+    /// assert_eq!(event.event_type, "Example.Created");
+    /// assert_eq!(event.data["name"], "Peter");
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> EventGridEventBuilder {
+        EventGridEventBuilder::new()
+    }
+}
+
+/// Represents a builder for Event Grid events.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct EventGridEventBuilder(EventGridEvent);
+
+#[cfg(feature = "testing")]
+impl EventGridEventBuilder {
+    /// Creates a new `EventGridEventBuilder` for an event that occurred now.
+    pub fn new() -> EventGridEventBuilder {
+        EventGridEventBuilder(EventGridEvent {
+            topic: String::new(),
+            subject: String::new(),
+            event_type: String::new(),
+            event_time: Utc::now(),
+            id: String::new(),
+            data: serde_json::Value::Null,
+            data_version: "1.0".to_owned(),
+            metadata_version: "1".to_owned(),
+        })
+    }
+
+    /// Sets the full resource path to the event source.
+    pub fn topic<T: Into<String>>(mut self, topic: T) -> Self {
+        self.0.topic = topic.into();
+        self
+    }
+
+    /// Sets the publisher-defined path to the event subject.
+    pub fn subject<T: Into<String>>(mut self, subject: T) -> Self {
+        self.0.subject = subject.into();
+        self
+    }
+
+    /// Sets the registered event type for this event source.
+    pub fn event_type<T: Into<String>>(mut self, event_type: T) -> Self {
+        self.0.event_type = event_type.into();
+        self
+    }
+
+    /// Sets the time the event is generated.
+    pub fn event_time(mut self, time: DateTime<Utc>) -> Self {
+        self.0.event_time = time;
+        self
+    }
+
+    /// Sets the unique identifier for the event.
+    pub fn id<T: Into<String>>(mut self, id: T) -> Self {
+        self.0.id = id.into();
+        self
+    }
+
+    /// Sets the event data specific to the resource provider.
+    pub fn data(mut self, data: serde_json::Value) -> Self {
+        self.0.data = data;
+        self
+    }
+
+    /// Sets the schema version of the data object.
+    pub fn data_version<T: Into<String>>(mut self, version: T) -> Self {
+        self.0.data_version = version.into();
+        self
+    }
+
+    /// Consumes the builder and returns the Event Grid event.
+    pub fn build(self) -> EventGridEvent {
+        self.0
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for EventGridEventBuilder {
+    fn default() -> Self {
+        EventGridEventBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for EventGridEventBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        let event = self.0;
+        (
+            json_data(&json!({
+                "topic": event.topic,
+                "subject": event.subject,
+                "eventType": event.event_type,
+                "eventTime": event.event_time.to_rfc3339(),
+                "id": event.id,
+                "data": event.data,
+                "dataVersion": event.data_version,
+                "metadataVersion": event.metadata_version,
+            })),
+            HashMap::new(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -8,6 +8,13 @@ use chrono::{DateTime, Utc};
 use serde_json::{from_str, Value};
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::{
+    event_hub::RuntimeInformation,
+    testing::BindingBuilder,
+    util::{int_data, json_data, string_data, time_data},
+};
+
 const PARTITION_CONTEXT_KEY: &str = "PartitionContext";
 const ENQUEUED_TIME_KEY: &str = "EnqueuedTimeUtc";
 const OFFSET_KEY: &str = "Offset";
@@ -118,6 +125,163 @@ impl EventHubTrigger {
             .expect("failed to deserialize system properties"),
         }
     }
+
+    /// Creates a builder for an Event Hub trigger.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::EventHubTrigger;
+    ///
+    /// let trigger = EventHubTrigger::builder()
+    ///     .message("hello")
+    ///     .sequence_number(3)
+    ///     .build();
+    ///
+    /// assert_eq!(trigger.message.as_str().unwrap(), "hello");
+    /// assert_eq!(trigger.sequence_number, 3);
+    /// assert_eq!(trigger.system_properties.sequence_number, 3);
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> EventHubTriggerBuilder {
+        EventHubTriggerBuilder::new()
+    }
+}
+
+/// Represents a builder for Event Hub trigger bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct EventHubTriggerBuilder {
+    data: TypedData,
+    partition_context: PartitionContext,
+    enqueued_time: DateTime<Utc>,
+    offset: String,
+    properties: Value,
+    sequence_number: i64,
+    partition_key: Option<String>,
+}
+
+#[cfg(feature = "testing")]
+impl EventHubTriggerBuilder {
+    /// Creates a new `EventHubTriggerBuilder`.
+    ///
+    /// The message is empty, was enqueued now and was received from partition `0`
+    /// of the `$Default` consumer group.
+    pub fn new() -> EventHubTriggerBuilder {
+        let now = Utc::now();
+
+        EventHubTriggerBuilder {
+            data: EventHubMessage::from("").into(),
+            partition_context: PartitionContext {
+                consumer_group_name: "$Default".to_owned(),
+                event_hub_path: String::new(),
+                partition_id: "0".to_owned(),
+                owner: String::new(),
+                runtime_information: RuntimeInformation {
+                    partition_id: "0".to_owned(),
+                    last_sequence_number: 0,
+                    last_enqueued_time: now,
+                    last_enqueued_offset: None,
+                    retrieval_time: now,
+                },
+            },
+            enqueued_time: now,
+            offset: "0".to_owned(),
+            properties: Value::Object(Default::default()),
+            sequence_number: 0,
+            partition_key: None,
+        }
+    }
+
+    /// Sets the Event Hub message that triggered the function.
+    pub fn message<T: Into<EventHubMessage>>(mut self, message: T) -> Self {
+        self.data = message.into().into();
+        self
+    }
+
+    /// Sets the partition context information.
+    pub fn partition_context(mut self, context: PartitionContext) -> Self {
+        self.partition_context = context;
+        self
+    }
+
+    /// Sets the enqueued time in UTC.
+    pub fn enqueued_time(mut self, time: DateTime<Utc>) -> Self {
+        self.enqueued_time = time;
+        self
+    }
+
+    /// Sets the offset of the data relative to the Event Hub partition stream.
+    pub fn offset<T: Into<String>>(mut self, offset: T) -> Self {
+        self.offset = offset.into();
+        self
+    }
+
+    /// Sets the user properties of the event data.
+    pub fn properties(mut self, properties: Value) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    /// Sets the logical sequence number of the event.
+    pub fn sequence_number(mut self, number: i64) -> Self {
+        self.sequence_number = number;
+        self
+    }
+
+    /// Sets the partition key of the event.
+    pub fn partition_key<T: Into<String>>(mut self, key: T) -> Self {
+        self.partition_key = Some(key.into());
+        self
+    }
+
+    /// Consumes the builder and returns the Event Hub trigger.
+    pub fn build(self) -> EventHubTrigger {
+        let (data, metadata) = self.into_data();
+        EventHubTrigger::new(data, metadata)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for EventHubTriggerBuilder {
+    fn default() -> Self {
+        EventHubTriggerBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for EventHubTriggerBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        let system_properties = SystemProperties {
+            sequence_number: self.sequence_number,
+            offset: self.offset.clone(),
+            partition_key: self.partition_key,
+            enqueued_time: self.enqueued_time,
+        };
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            PARTITION_CONTEXT_KEY.to_owned(),
+            json_data(&self.partition_context),
+        );
+        metadata.insert(ENQUEUED_TIME_KEY.to_owned(), time_data(self.enqueued_time));
+        metadata.insert(OFFSET_KEY.to_owned(), string_data(self.offset));
+        metadata.insert(PROPERTIES_KEY.to_owned(), json_data(&self.properties));
+        metadata.insert(
+            SEQUENCE_NUMBER_KEY.to_owned(),
+            int_data(self.sequence_number),
+        );
+        metadata.insert(
+            SYSTEM_PROPERTIES_KEY.to_owned(),
+            json_data(&system_properties),
+        );
+
+        (self.data, metadata)
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,10 @@
 use crate::{generic::Value, http::Body, rpc::TypedData};
 
+#[cfg(feature = "testing")]
+use crate::testing::BindingBuilder;
+#[cfg(feature = "testing")]
+use std::collections::HashMap;
+
 /// Represents a generic input binding.
 ///
 /// The following binding attributes are supported:
@@ -70,6 +75,69 @@ impl From<TypedData> for GenericInput {
     }
 }
 
+#[cfg(feature = "testing")]
+impl GenericInput {
+    /// Creates a builder for a generic input binding.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::{bindings::GenericInput, generic::Value};
+    /// use serde_json::json;
+    ///
+    /// let input = GenericInput::builder()
+    ///     .data(Value::Json(json!({ "foo": "bar" })))
+    ///     .build();
+    ///
+    /// assert_eq!(input.data, Value::Json(json!({ "foo": "bar" })));
+    /// ```
+    pub fn builder() -> GenericInputBuilder {
+        GenericInputBuilder::new()
+    }
+}
+
+/// Represents a builder for generic input bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct GenericInputBuilder(GenericInput);
+
+#[cfg(feature = "testing")]
+impl GenericInputBuilder {
+    /// Creates a new `GenericInputBuilder` with no data.
+    pub fn new() -> GenericInputBuilder {
+        GenericInputBuilder(GenericInput { data: Value::None })
+    }
+
+    /// Sets the input binding data.
+    pub fn data(mut self, data: Value) -> Self {
+        self.0.data = data;
+        self
+    }
+
+    /// Consumes the builder and returns the generic input binding.
+    pub fn build(self) -> GenericInput {
+        self.0
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for GenericInputBuilder {
+    fn default() -> Self {
+        GenericInputBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for GenericInputBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (self.0.data.into(), HashMap::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,9 @@
 use crate::{generic::Value, rpc::TypedData};
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::testing::BindingBuilder;
+
 /// Represents a generic trigger binding.
 ///
 /// The following binding attributes are supported:
@@ -57,6 +60,85 @@ impl GenericTrigger {
             metadata: md,
         }
     }
+
+    /// Creates a builder for a generic trigger.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::{bindings::GenericTrigger, generic::Value};
+    /// use serde_json::json;
+    ///
+    /// let trigger = GenericTrigger::builder()
+    ///     .data(Value::Json(json!({ "foo": "bar" })))
+    ///     .metadata("key", Value::String("value".to_owned()))
+    ///     .build();
+    ///
+    /// assert_eq!(trigger.data, Value::Json(json!({ "foo": "bar" })));
+    /// assert_eq!(trigger.metadata["key"], Value::String("value".to_owned()));
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> GenericTriggerBuilder {
+        GenericTriggerBuilder::new()
+    }
+}
+
+/// Represents a builder for generic trigger bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct GenericTriggerBuilder(GenericTrigger);
+
+#[cfg(feature = "testing")]
+impl GenericTriggerBuilder {
+    /// Creates a new `GenericTriggerBuilder` with no data.
+    pub fn new() -> GenericTriggerBuilder {
+        GenericTriggerBuilder(GenericTrigger {
+            data: Value::None,
+            metadata: HashMap::new(),
+        })
+    }
+
+    /// Sets the trigger binding data.
+    pub fn data(mut self, data: Value) -> Self {
+        self.0.data = data;
+        self
+    }
+
+    /// Sets a trigger metadata value.
+    pub fn metadata<T: Into<String>>(mut self, name: T, value: Value) -> Self {
+        self.0.metadata.insert(name.into(), value);
+        self
+    }
+
+    /// Consumes the builder and returns the generic trigger.
+    pub fn build(self) -> GenericTrigger {
+        self.0
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for GenericTriggerBuilder {
+    fn default() -> Self {
+        GenericTriggerBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for GenericTriggerBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (
+            self.0.data.into(),
+            self.0
+                .metadata
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+        )
+    }
 }
 
 #[cfg(test)]
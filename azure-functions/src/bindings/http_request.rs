@@ -1,9 +1,12 @@
 use crate::{
     http::Body,
-    rpc::{typed_data::Data, RpcHttp, TypedData},
+    rpc::{nullable_string, typed_data::Data, NullableString, RpcHttp, TypedData},
 };
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::testing::BindingBuilder;
+
 /// Represents a HTTP trigger binding.
 ///
 /// The following binding attributes are supported:
@@ -40,11 +43,45 @@ impl HttpRequest {
     #[doc(hidden)]
     pub fn new(data: TypedData, _: HashMap<String, TypedData>) -> Self {
         match data.data {
-            Some(Data::Http(http)) => HttpRequest(*http),
+            Some(Data::Http(mut http)) => {
+                // Hosts using nullable value dictionaries send the headers and parameters only in those
+                merge_nullable(
+                    &mut http.headers,
+                    std::mem::take(&mut http.nullable_headers),
+                );
+                merge_nullable(&mut http.params, std::mem::take(&mut http.nullable_params));
+                merge_nullable(&mut http.query, std::mem::take(&mut http.nullable_query));
+                HttpRequest(*http)
+            }
             _ => panic!("unexpected type data for HTTP request."),
         }
     }
 
+    /// Creates a builder for a HTTP request.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::HttpRequest;
+    /// use serde_json::json;
+    ///
+    /// let request = HttpRequest::builder()
+    ///     .method("POST")
+    ///     .json(&json!({ "name": "Peter" }))
+    ///     .route_param("id", "1")
+    ///     .build();
+    ///
+    /// assert_eq!(request.method(), "POST");
+    /// assert_eq!(request.route_params().get("id").unwrap(), "1");
+    /// assert_eq!(request.body().as_json::<serde_json::Value>().unwrap()["name"], "Peter");
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> HttpRequestBuilder {
+        HttpRequestBuilder::new()
+    }
+
     /// Gets the HTTP method (e.g. "GET") for the request.
     pub fn method(&self) -> &str {
         &self.0.method
@@ -122,6 +159,126 @@ impl HttpRequest {
     }
 }
 
+fn merge_nullable(map: &mut HashMap<String, String>, nullable: HashMap<String, NullableString>) {
+    for (key, value) in nullable {
+        let value = match value.string {
+            Some(nullable_string::String::Value(s)) => s,
+            None => String::new(),
+        };
+        map.entry(key).or_insert(value);
+    }
+}
+
+/// Represents a builder for HTTP requests.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct HttpRequestBuilder(RpcHttp);
+
+#[cfg(feature = "testing")]
+impl HttpRequestBuilder {
+    /// Creates a new `HttpRequestBuilder` for a `GET` request.
+    pub fn new() -> HttpRequestBuilder {
+        HttpRequestBuilder(RpcHttp {
+            method: "GET".to_owned(),
+            url: "http://localhost/".to_owned(),
+            ..Default::default()
+        })
+    }
+
+    /// Sets the HTTP method (e.g. "POST") for the request.
+    pub fn method<T: Into<String>>(mut self, method: T) -> Self {
+        self.0.method = method.into();
+        self
+    }
+
+    /// Sets the URL of the request.
+    pub fn url<T: Into<String>>(mut self, url: T) -> Self {
+        self.0.url = url.into();
+        self
+    }
+
+    /// Sets a header for the request.
+    ///
+    /// The header key is lower-cased, as it is by the Azure Functions Host.
+    pub fn header<T: Into<String>, U: Into<String>>(mut self, name: T, value: U) -> Self {
+        self.0
+            .headers
+            .insert(name.into().to_lowercase(), value.into());
+        self
+    }
+
+    /// Sets a route parameter for the request.
+    pub fn route_param<T: Into<String>, U: Into<String>>(mut self, name: T, value: U) -> Self {
+        self.0.params.insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets a query parameter for the request.
+    pub fn query_param<T: Into<String>, U: Into<String>>(mut self, name: T, value: U) -> Self {
+        self.0.query.insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets the body of the request.
+    ///
+    /// This will automatically set a `Content-Type` header for the request depending on the body type.
+    pub fn body<'a, B>(mut self, body: B) -> Self
+    where
+        B: Into<Body<'a>>,
+    {
+        let body = body.into();
+        if let Body::Empty = &body {
+            self.0.body = None;
+            return self;
+        }
+
+        if !self.0.headers.contains_key("content-type") {
+            self.0.headers.insert(
+                "content-type".to_string(),
+                body.default_content_type().to_string(),
+            );
+        }
+        self.0.body = Some(Box::new(body.into()));
+        self
+    }
+
+    /// Sets the body of the request to the given value serialized as JSON.
+    pub fn json<T: serde::Serialize + ?Sized>(self, value: &T) -> Self {
+        self.body(Body::Json(
+            serde_json::to_string(value)
+                .expect("failed to serialize JSON body")
+                .into(),
+        ))
+    }
+
+    /// Consumes the builder and returns the HTTP request.
+    pub fn build(self) -> HttpRequest {
+        let (data, metadata) = self.into_data();
+        HttpRequest::new(data, metadata)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for HttpRequestBuilder {
+    fn default() -> Self {
+        HttpRequestBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for HttpRequestBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (
+            TypedData {
+                data: Some(Data::Http(Box::new(self.0))),
+            },
+            HashMap::new(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +363,30 @@ mod tests {
         assert_eq!(request.query_params().get(KEY).unwrap(), VALUE);
     }
 
+    #[test]
+    fn it_has_nullable_headers_and_parameters() {
+        let nullable = |s: Option<&str>| NullableString {
+            string: s.map(|s| nullable_string::String::Value(s.to_owned())),
+        };
+
+        let mut http = RpcHttp::default();
+        http.nullable_headers
+            .insert("accept".to_string(), nullable(Some("text/plain")));
+        http.nullable_params
+            .insert("id".to_string(), nullable(Some("1")));
+        http.nullable_query
+            .insert("flag".to_string(), nullable(None));
+
+        let data = TypedData {
+            data: Some(Data::Http(Box::new(http))),
+        };
+
+        let request = HttpRequest::new(data, HashMap::new());
+        assert_eq!(request.headers().get("accept").unwrap(), "text/plain");
+        assert_eq!(request.route_params().get("id").unwrap(), "1");
+        assert_eq!(request.query_params().get("flag").unwrap(), "");
+    }
+
     #[test]
     fn it_has_an_empty_body() {
         let data = TypedData {
@@ -283,4 +464,25 @@ mod tests {
         let request = HttpRequest::new(data, HashMap::new());
         assert!(matches!(request.body(), Body::Bytes(Cow::Borrowed(BODY))));
     }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn it_builds_a_request() {
+        let request = HttpRequest::builder()
+            .method("POST")
+            .url("http://example.com/api/users/1")
+            .header("X-Custom-Header", "value")
+            .route_param("id", "1")
+            .query_param("name", "Peter")
+            .body("hello")
+            .build();
+
+        assert_eq!(request.method(), "POST");
+        assert_eq!(request.url(), "http://example.com/api/users/1");
+        assert_eq!(request.headers().get("x-custom-header").unwrap(), "value");
+        assert_eq!(request.headers().get("content-type").unwrap(), "text/plain");
+        assert_eq!(request.route_params().get("id").unwrap(), "1");
+        assert_eq!(request.query_params().get("name").unwrap(), "Peter");
+        assert_eq!(request.body().as_str().unwrap(), "hello");
+    }
 }
@@ -166,6 +166,24 @@ impl Into<TypedData> for HttpResponse {
     }
 }
 
+#[cfg(feature = "testing")]
+#[doc(hidden)]
+impl From<TypedData> for HttpResponse {
+    fn from(data: TypedData) -> Self {
+        match data.data {
+            Some(Data::Http(http)) => HttpResponse {
+                status: http
+                    .status_code
+                    .parse::<u16>()
+                    .map(Status::from)
+                    .unwrap_or(Status::Ok),
+                data: *http,
+            },
+            _ => panic!("expected HTTP data for HTTP response"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
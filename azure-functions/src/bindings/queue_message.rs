@@ -1,6 +1,7 @@
 use crate::{
     http::Body,
     rpc::{typed_data::Data, TypedData},
+    util::into_collection,
     FromVec,
 };
 use serde::de::Error;
@@ -166,10 +167,15 @@ impl From<TypedData> for QueueMessage {
 #[doc(hidden)]
 impl FromVec<QueueMessage> for TypedData {
     fn from_vec(vec: Vec<QueueMessage>) -> Self {
-        TypedData {
-            data: Some(Data::Json(
-                Value::Array(vec.into_iter().map(Into::into).collect()).to_string(),
-            )),
+        // Messages are sent as a collection if the host accepts them, preserving binary messages
+        match into_collection(vec.into_iter().map(|m| m.0).collect()) {
+            Ok(data) => data,
+            Err(values) => TypedData {
+                data: Some(Data::Json(
+                    Value::Array(values.into_iter().map(|v| QueueMessage(v).into()).collect())
+                        .to_string(),
+                )),
+            },
         }
     }
 }
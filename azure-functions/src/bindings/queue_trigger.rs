@@ -6,6 +6,12 @@ use crate::{
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::{
+    testing::BindingBuilder,
+    util::{int_data, string_data, time_data},
+};
+
 const ID_KEY: &str = "Id";
 const DEQUEUE_COUNT_KEY: &str = "DequeueCount";
 const EXPIRATION_TIME_KEY: &str = "ExpirationTime";
@@ -101,6 +107,133 @@ impl QueueTrigger {
                 .expect("expected a pop receipt"),
         }
     }
+
+    /// Creates a builder for a queue trigger.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::QueueTrigger;
+    ///
+    /// let trigger = QueueTrigger::builder()
+    ///     .message("hello")
+    ///     .id("1")
+    ///     .dequeue_count(2)
+    ///     .build();
+    ///
+    /// assert_eq!(trigger.message.as_str().unwrap(), "hello");
+    /// assert_eq!(trigger.id, "1");
+    /// assert_eq!(trigger.dequeue_count, 2);
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> QueueTriggerBuilder {
+        QueueTriggerBuilder::new()
+    }
+}
+
+/// Represents a builder for queue trigger bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct QueueTriggerBuilder {
+    data: TypedData,
+    metadata: HashMap<String, TypedData>,
+}
+
+#[cfg(feature = "testing")]
+impl QueueTriggerBuilder {
+    /// Creates a new `QueueTriggerBuilder`.
+    ///
+    /// The message is empty, the message was inserted now, expires in seven days
+    /// and has been dequeued once.
+    pub fn new() -> QueueTriggerBuilder {
+        let now = Utc::now();
+
+        let mut metadata = HashMap::new();
+        metadata.insert(ID_KEY.to_owned(), string_data(""));
+        metadata.insert(DEQUEUE_COUNT_KEY.to_owned(), int_data(1));
+        metadata.insert(
+            EXPIRATION_TIME_KEY.to_owned(),
+            time_data(now + chrono::Duration::days(7)),
+        );
+        metadata.insert(INSERTION_TIME_KEY.to_owned(), time_data(now));
+        metadata.insert(NEXT_VISIBLE_TIME_KEY.to_owned(), time_data(now));
+        metadata.insert(POP_RECEIPT_KEY.to_owned(), string_data(""));
+
+        QueueTriggerBuilder {
+            data: QueueMessage::from("").into(),
+            metadata,
+        }
+    }
+
+    /// Sets the queue message that triggered the function.
+    pub fn message<T: Into<QueueMessage>>(mut self, message: T) -> Self {
+        self.data = message.into().into();
+        self
+    }
+
+    /// Sets the queue message identifier.
+    pub fn id<T: Into<String>>(mut self, id: T) -> Self {
+        self.metadata.insert(ID_KEY.to_owned(), string_data(id));
+        self
+    }
+
+    /// Sets the number of times the message has been dequeued.
+    pub fn dequeue_count(mut self, count: u32) -> Self {
+        self.metadata
+            .insert(DEQUEUE_COUNT_KEY.to_owned(), int_data(count));
+        self
+    }
+
+    /// Sets the time that the message expires.
+    pub fn expiration_time(mut self, time: DateTime<Utc>) -> Self {
+        self.metadata
+            .insert(EXPIRATION_TIME_KEY.to_owned(), time_data(time));
+        self
+    }
+
+    /// Sets the time that the message was added to the queue.
+    pub fn insertion_time(mut self, time: DateTime<Utc>) -> Self {
+        self.metadata
+            .insert(INSERTION_TIME_KEY.to_owned(), time_data(time));
+        self
+    }
+
+    /// Sets the time that the message will next be visible.
+    pub fn next_visible_time(mut self, time: DateTime<Utc>) -> Self {
+        self.metadata
+            .insert(NEXT_VISIBLE_TIME_KEY.to_owned(), time_data(time));
+        self
+    }
+
+    /// Sets the message's pop receipt.
+    pub fn pop_receipt<T: Into<String>>(mut self, receipt: T) -> Self {
+        self.metadata
+            .insert(POP_RECEIPT_KEY.to_owned(), string_data(receipt));
+        self
+    }
+
+    /// Consumes the builder and returns the queue trigger.
+    pub fn build(self) -> QueueTrigger {
+        QueueTrigger::new(self.data, self.metadata)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for QueueTriggerBuilder {
+    fn default() -> Self {
+        QueueTriggerBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for QueueTriggerBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (self.data, self.metadata)
+    }
 }
 
 #[cfg(test)]
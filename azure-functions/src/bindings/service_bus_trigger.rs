@@ -7,6 +7,12 @@ use chrono::{DateTime, Utc};
 use serde_json::{from_str, Map, Value};
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::{
+    testing::BindingBuilder,
+    util::{int_data, json_data, string_data, time_data},
+};
+
 const DELIVERY_COUNT_KEY: &str = "DeliveryCount";
 const DEAD_LETTER_SOURCE_KEY: &str = "DeadLetterSource";
 const EXPIRATION_TIME_KEY: &str = "ExpiresAtUtc";
@@ -173,6 +179,180 @@ impl ServiceBusTrigger {
             .expect("failed to convert user properties"),
         }
     }
+
+    /// Creates a builder for a service bus trigger.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::ServiceBusTrigger;
+    ///
+    /// let trigger = ServiceBusTrigger::builder()
+    ///     .message("hello")
+    ///     .message_id("1")
+    ///     .user_property("priority", "high")
+    ///     .build();
+    ///
+    /// assert_eq!(trigger.message.as_str().unwrap(), "hello");
+    /// assert_eq!(trigger.message_id, "1");
+    /// assert_eq!(trigger.user_properties["priority"], "high");
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> ServiceBusTriggerBuilder {
+        ServiceBusTriggerBuilder::new()
+    }
+}
+
+/// Represents a builder for service bus trigger bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct ServiceBusTriggerBuilder {
+    data: TypedData,
+    metadata: HashMap<String, TypedData>,
+    user_properties: Map<String, Value>,
+}
+
+#[cfg(feature = "testing")]
+impl ServiceBusTriggerBuilder {
+    /// Creates a new `ServiceBusTriggerBuilder`.
+    ///
+    /// The message is empty, the message was enqueued now, expires in fourteen days
+    /// and has been delivered once.
+    pub fn new() -> ServiceBusTriggerBuilder {
+        let now = Utc::now();
+
+        let mut metadata = HashMap::new();
+        metadata.insert(DELIVERY_COUNT_KEY.to_owned(), int_data(1));
+        metadata.insert(
+            EXPIRATION_TIME_KEY.to_owned(),
+            time_data(now + chrono::Duration::days(14)),
+        );
+        metadata.insert(ENQUEUED_TIME_KEY.to_owned(), time_data(now));
+        metadata.insert(MESSAGE_ID_KEY.to_owned(), string_data(""));
+        metadata.insert(SEQUENCE_NUMBER_KEY.to_owned(), int_data(1));
+
+        ServiceBusTriggerBuilder {
+            data: ServiceBusMessage::from("").into(),
+            metadata,
+            user_properties: Map::new(),
+        }
+    }
+
+    /// Sets the message that triggered the function.
+    pub fn message<T: Into<ServiceBusMessage>>(mut self, message: T) -> Self {
+        self.data = message.into().into();
+        self
+    }
+
+    /// Sets the number of deliveries.
+    pub fn delivery_count(mut self, count: i32) -> Self {
+        self.metadata
+            .insert(DELIVERY_COUNT_KEY.to_owned(), int_data(count));
+        self
+    }
+
+    /// Sets the name of the queue or subscription the message was dead-lettered from.
+    pub fn dead_letter_source<T: Into<String>>(mut self, source: T) -> Self {
+        self.metadata
+            .insert(DEAD_LETTER_SOURCE_KEY.to_owned(), string_data(source));
+        self
+    }
+
+    /// Sets the date and time in UTC at which the message expires.
+    pub fn expiration_time(mut self, time: DateTime<Utc>) -> Self {
+        self.metadata
+            .insert(EXPIRATION_TIME_KEY.to_owned(), time_data(time));
+        self
+    }
+
+    /// Sets the date and time in UTC at which the message was enqueued.
+    pub fn enqueued_time(mut self, time: DateTime<Utc>) -> Self {
+        self.metadata
+            .insert(ENQUEUED_TIME_KEY.to_owned(), time_data(time));
+        self
+    }
+
+    /// Sets the user-defined value that Service Bus can use to identify duplicate messages.
+    pub fn message_id<T: Into<String>>(mut self, id: T) -> Self {
+        self.metadata
+            .insert(MESSAGE_ID_KEY.to_owned(), string_data(id));
+        self
+    }
+
+    /// Sets the content type of the message.
+    pub fn content_type<T: Into<String>>(mut self, content_type: T) -> Self {
+        self.metadata
+            .insert(CONTENT_TYPE_KEY.to_owned(), string_data(content_type));
+        self
+    }
+
+    /// Sets the reply to queue address.
+    pub fn reply_to<T: Into<String>>(mut self, reply_to: T) -> Self {
+        self.metadata
+            .insert(REPLY_TO_KEY.to_owned(), string_data(reply_to));
+        self
+    }
+
+    /// Sets the unique number assigned to the message by Service Bus.
+    pub fn sequence_number(mut self, number: i64) -> Self {
+        self.metadata
+            .insert(SEQUENCE_NUMBER_KEY.to_owned(), int_data(number));
+        self
+    }
+
+    /// Sets the send to address.
+    pub fn to<T: Into<String>>(mut self, to: T) -> Self {
+        self.metadata.insert(TO_KEY.to_owned(), string_data(to));
+        self
+    }
+
+    /// Sets the application specific label.
+    pub fn label<T: Into<String>>(mut self, label: T) -> Self {
+        self.metadata
+            .insert(LABEL_KEY.to_owned(), string_data(label));
+        self
+    }
+
+    /// Sets the correlation identifier.
+    pub fn correlation_id<T: Into<String>>(mut self, id: T) -> Self {
+        self.metadata
+            .insert(CORRELATION_ID_KEY.to_owned(), string_data(id));
+        self
+    }
+
+    /// Sets an application specific message property.
+    pub fn user_property<T: Into<String>, U: Into<Value>>(mut self, name: T, value: U) -> Self {
+        self.user_properties.insert(name.into(), value.into());
+        self
+    }
+
+    /// Consumes the builder and returns the service bus trigger.
+    pub fn build(self) -> ServiceBusTrigger {
+        let (data, metadata) = self.into_data();
+        ServiceBusTrigger::new(data, metadata)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for ServiceBusTriggerBuilder {
+    fn default() -> Self {
+        ServiceBusTriggerBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for ServiceBusTriggerBuilder {
+    fn into_data(mut self) -> (TypedData, HashMap<String, TypedData>) {
+        self.metadata.insert(
+            USER_PROPERTIES_KEY.to_owned(),
+            json_data(&self.user_properties),
+        );
+        (self.data, self.metadata)
+    }
 }
 
 #[cfg(test)]
@@ -6,6 +6,11 @@ use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_string};
 use std::borrow::Cow;
 
+#[cfg(feature = "testing")]
+use crate::{testing::BindingBuilder, util::json_data};
+#[cfg(feature = "testing")]
+use std::collections::HashMap;
+
 /// Represents the SignalR connection information input binding.
 ///
 /// The following binding attributes are supported:
@@ -66,6 +71,78 @@ impl<'a> Into<Body<'a>> for SignalRConnectionInfo {
     }
 }
 
+#[cfg(feature = "testing")]
+impl SignalRConnectionInfo {
+    /// Creates a builder for SignalR connection information.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::SignalRConnectionInfo;
+    ///
+    /// let info = SignalRConnectionInfo::builder()
+    ///     .url("https://example.service.signalr.net/client/?hub=chat")
+    ///     .access_token("token")
+    ///     .build();
+    ///
+    /// assert_eq!(info.access_token, "token");
+    /// ```
+    pub fn builder() -> SignalRConnectionInfoBuilder {
+        SignalRConnectionInfoBuilder::new()
+    }
+}
+
+/// Represents a builder for SignalR connection information.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct SignalRConnectionInfoBuilder(SignalRConnectionInfo);
+
+#[cfg(feature = "testing")]
+impl SignalRConnectionInfoBuilder {
+    /// Creates a new `SignalRConnectionInfoBuilder` with an empty URL and access token.
+    pub fn new() -> SignalRConnectionInfoBuilder {
+        SignalRConnectionInfoBuilder(SignalRConnectionInfo {
+            url: String::new(),
+            access_token: String::new(),
+        })
+    }
+
+    /// Sets the URL for a client to connect to the SignalR Service.
+    pub fn url<T: Into<String>>(mut self, url: T) -> Self {
+        self.0.url = url.into();
+        self
+    }
+
+    /// Sets the access token for a client to use to connect to the SignalR Service.
+    pub fn access_token<T: Into<String>>(mut self, token: T) -> Self {
+        self.0.access_token = token.into();
+        self
+    }
+
+    /// Consumes the builder and returns the SignalR connection information.
+    pub fn build(self) -> SignalRConnectionInfo {
+        self.0
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for SignalRConnectionInfoBuilder {
+    fn default() -> Self {
+        SignalRConnectionInfoBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for SignalRConnectionInfoBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (json_data(&self.0), HashMap::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -5,6 +5,11 @@ use crate::{
 use serde_json::{from_str, json, Map, Value};
 use std::fmt;
 
+#[cfg(feature = "testing")]
+use crate::testing::BindingBuilder;
+#[cfg(feature = "testing")]
+use std::collections::HashMap;
+
 /// Represents an Azure Storage table input or output binding.
 ///
 /// The following binding attributes are supported:
@@ -63,6 +68,27 @@ impl Table {
         Table(Value::Array(Vec::new()))
     }
 
+    /// Creates a builder for a table input binding.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::Table;
+    /// use serde_json::json;
+    ///
+    /// let table = Table::builder()
+    ///     .row(json!({ "PartitionKey": "partition", "RowKey": "1", "name": "Peter" }))
+    ///     .build();
+    ///
+    /// assert_eq!(table.len(), 1);
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> TableBuilder {
+        TableBuilder::new()
+    }
+
     /// Gets whether or not the table binding is empty (no rows).
     pub fn is_empty(&self) -> bool {
         self.0.as_array().unwrap().is_empty()
@@ -162,6 +188,48 @@ impl Into<TypedData> for Table {
     }
 }
 
+/// Represents a builder for table input bindings.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct TableBuilder(Table);
+
+#[cfg(feature = "testing")]
+impl TableBuilder {
+    /// Creates a new `TableBuilder` with no rows.
+    pub fn new() -> TableBuilder {
+        TableBuilder(Table::new())
+    }
+
+    /// Adds a row to the table.
+    ///
+    /// The row is expected to be a JSON object with `PartitionKey` and `RowKey` properties.
+    pub fn row(mut self, row: Value) -> Self {
+        self.0.add_row_value(row);
+        self
+    }
+
+    /// Consumes the builder and returns the table.
+    pub fn build(self) -> Table {
+        self.0
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for TableBuilder {
+    fn default() -> Self {
+        TableBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for TableBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        (self.0.into(), HashMap::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
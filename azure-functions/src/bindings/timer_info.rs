@@ -6,6 +6,13 @@ use serde::Deserialize;
 use serde_json::from_str;
 use std::collections::HashMap;
 
+#[cfg(feature = "testing")]
+use crate::{testing::BindingBuilder, util::json_data};
+#[cfg(feature = "testing")]
+use chrono::Utc;
+#[cfg(feature = "testing")]
+use serde_json::json;
+
 /// Represents the timer information from a timer trigger binding.
 ///
 /// The following binding attributes are supported:
@@ -51,6 +58,92 @@ impl TimerInfo {
             _ => panic!("expected JSON data for timer trigger binding"),
         }
     }
+
+    /// Creates a builder for timer information.
+    ///
+    /// This function requires the `testing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::bindings::TimerInfo;
+    ///
+    /// let info = TimerInfo::builder().is_past_due(true).build();
+    ///
+    /// assert!(info.is_past_due);
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn builder() -> TimerInfoBuilder {
+        TimerInfoBuilder::new()
+    }
+}
+
+/// Represents a builder for timer information.
+///
+/// This type requires the `testing` feature.
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub struct TimerInfoBuilder(TimerInfo);
+
+#[cfg(feature = "testing")]
+impl TimerInfoBuilder {
+    /// Creates a new `TimerInfoBuilder`.
+    ///
+    /// The timer is not past due and all times of the schedule status are now.
+    pub fn new() -> TimerInfoBuilder {
+        let now = Utc::now();
+
+        TimerInfoBuilder(TimerInfo {
+            schedule_status: ScheduleStatus {
+                last: now,
+                next: now,
+                last_updated: now,
+            },
+            is_past_due: false,
+        })
+    }
+
+    /// Sets the schedule status for the timer.
+    pub fn schedule_status(mut self, status: ScheduleStatus) -> Self {
+        self.0.schedule_status = status;
+        self
+    }
+
+    /// Sets whether the timer invocation is due to a missed schedule occurrence.
+    pub fn is_past_due(mut self, is_past_due: bool) -> Self {
+        self.0.is_past_due = is_past_due;
+        self
+    }
+
+    /// Consumes the builder and returns the timer information.
+    pub fn build(self) -> TimerInfo {
+        self.0
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Default for TimerInfoBuilder {
+    fn default() -> Self {
+        TimerInfoBuilder::new()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl BindingBuilder for TimerInfoBuilder {
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>) {
+        let status = &self.0.schedule_status;
+        (
+            json_data(&json!({
+                "ScheduleStatus": {
+                    "Last": status.last.to_rfc3339(),
+                    "Next": status.next.to_rfc3339(),
+                    "LastUpdated": status.last_updated.to_rfc3339(),
+                },
+                "IsPastDue": self.0.is_past_due,
+            })),
+            HashMap::new(),
+        )
+    }
 }
 
 #[cfg(test)]
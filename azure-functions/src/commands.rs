@@ -1,7 +1,11 @@
 mod init;
 mod run;
+mod serve;
+mod serve_http;
 mod sync_extensions;
 
 pub use self::init::*;
 pub use self::run::*;
+pub use self::serve::*;
+pub use self::serve_http::*;
 pub use self::sync_extensions::*;
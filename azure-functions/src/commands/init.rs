@@ -1,7 +1,7 @@
 use crate::{codegen::Function, commands::SyncExtensions, registry::Registry};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use serde::Serialize;
-use serde_json::{json, to_string_pretty, Serializer};
+use serde_json::{from_str, json, to_string_pretty, Serializer, Value};
 use std::env::{self, current_dir, current_exe};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -11,6 +11,8 @@ pub struct Init<'a> {
     pub local_settings: Option<&'a str>,
     pub host_settings: Option<&'a str>,
     pub sync_extensions: bool,
+    pub custom_handler: bool,
+    pub worker_indexing: bool,
     pub no_debug_info: bool,
     pub verbose: bool,
 }
@@ -58,6 +60,17 @@ impl<'a> Init<'a> {
                         .short('s')
                         .help("Synchronize the Azure Function binding extensions.")
                 )
+                .arg(
+                    Arg::with_name("custom_handler")
+                        .long("custom-handler")
+                        .help("Initialize the application to run as a custom handler rather than as a language worker.")
+                )
+                .arg(
+                    Arg::with_name("worker_indexing")
+                        .long("worker-indexing")
+                        .conflicts_with("custom_handler")
+                        .help("Have the worker report its functions to the host rather than creating a `function.json` file for each function.")
+                )
                 .arg(
                     Arg::with_name("no_debug_info")
                         .long("--no-debug-info")
@@ -100,11 +113,22 @@ impl<'a> Init<'a> {
             self.copy_worker_debug_info(&current_exe, &worker_exe);
         }
 
-        self.create_worker_config_file(&worker_dir, &worker_exe);
+        if self.custom_handler {
+            self.add_custom_handler_settings(&worker_exe);
+        } else {
+            self.create_worker_config_file(&worker_dir, &worker_exe);
+        }
 
         self.delete_existing_function_directories();
 
-        for (name, info) in registry.iter() {
+        // With worker indexing, the host requests the function metadata from the worker
+        let functions = if self.worker_indexing {
+            Vec::new()
+        } else {
+            registry.iter().collect()
+        };
+
+        for (name, info) in functions {
             let function_dir = self.create_function_directory(name);
 
             let source_file = Init::get_source_file_path(
@@ -261,9 +285,15 @@ impl<'a> Init<'a> {
             to_string_pretty(&json!(
             {
                 "IsEncrypted": false,
-                "Values": {
-                    "FUNCTIONS_WORKER_RUNTIME": "Rust",
-                    "languageWorkers:workersDirectory": "workers"
+                "Values": if self.custom_handler {
+                    json!({
+                        "FUNCTIONS_WORKER_RUNTIME": "custom"
+                    })
+                } else {
+                    json!({
+                        "FUNCTIONS_WORKER_RUNTIME": "Rust",
+                        "languageWorkers:workersDirectory": "workers"
+                    })
                 },
                 "ConnectionStrings": {
                 }
@@ -371,22 +401,63 @@ impl<'a> Init<'a> {
             println!("Creating worker config file '{}'.", config.display());
         }
 
+        let mut description = json!({
+            "language": "Rust",
+            "extensions": [".rs"],
+            "defaultExecutablePath": worker_exe.to_str().unwrap(),
+            "arguments": ["run"]
+        });
+
+        if self.worker_indexing {
+            description["workerIndexing"] = json!("true");
+        }
+
         fs::write(
             &config,
-            to_string_pretty(&json!(
-            {
-                "description":{
-                    "language": "Rust",
-                    "extensions": [".rs"],
-                    "defaultExecutablePath": worker_exe.to_str().unwrap(),
-                    "arguments": ["run"]
-                }
-            }))
-            .unwrap(),
+            to_string_pretty(&json!({ "description": description })).unwrap(),
         )
         .unwrap_or_else(|e| panic!("failed to create '{}': {}", config.display(), e));
     }
 
+    fn add_custom_handler_settings(&self, worker_exe: &Path) {
+        let settings = self.script_root.join("host.json");
+
+        if self.verbose {
+            println!(
+                "Adding custom handler settings to host settings file '{}'.",
+                settings.display()
+            );
+        }
+
+        let mut host: Value = from_str(
+            &fs::read_to_string(&settings)
+                .unwrap_or_else(|e| panic!("failed to read '{}': {}", settings.display(), e)),
+        )
+        .unwrap_or_else(|e| panic!("failed to parse '{}': {}", settings.display(), e));
+
+        // The executable path is relative to the script root so the application can be deployed
+        let worker_exe = worker_exe
+            .strip_prefix(&self.script_root)
+            .expect("expected the worker executable to be in the script root");
+
+        host.as_object_mut()
+            .unwrap_or_else(|| panic!("expected '{}' to be a JSON object", settings.display()))
+            .insert(
+                "customHandler".to_owned(),
+                json!({
+                    "description": {
+                        "defaultExecutablePath": worker_exe.to_str().unwrap(),
+                        "workingDirectory": "",
+                        "arguments": ["serve"]
+                    },
+                    "enableForwardingHttpRequest": false
+                }),
+            );
+
+        fs::write(&settings, to_string_pretty(&host).unwrap())
+            .unwrap_or_else(|e| panic!("failed to update '{}': {}", settings.display(), e));
+    }
+
     fn delete_existing_function_directories(&self) {
         for entry in fs::read_dir(&self.script_root).expect("failed to read script root directory")
         {
@@ -539,6 +610,8 @@ impl<'a> From<&'a ArgMatches> for Init<'a> {
             local_settings: args.value_of("local_settings"),
             host_settings: args.value_of("host_settings"),
             sync_extensions: args.is_present("sync_extensions"),
+            custom_handler: args.is_present("custom_handler"),
+            worker_indexing: args.is_present("worker_indexing"),
             no_debug_info: args.is_present("no_debug_info"),
             verbose: args.is_present("verbose"),
         }
@@ -1,10 +1,11 @@
-use crate::{registry::Registry, worker::Worker};
+use crate::{hooks::Hooks, registry::Registry, worker::Worker};
 use clap::{App, Arg, ArgMatches, SubCommand};
 
 pub struct Run<'a> {
     pub host: &'a str,
     pub port: u16,
     pub worker_id: &'a str,
+    pub max_message_length: Option<usize>,
 }
 
 impl<'a> Run<'a> {
@@ -44,16 +45,23 @@ impl<'a> Run<'a> {
                 Arg::with_name("max_message_length")
                     .long("grpcMaxMessageLength")
                     .value_name("MAXIMUM")
-                    .help("The maximum message length to use for gRPC messages."),
+                    .help(
+                        "The maximum message length to use for gRPC messages. \
+                         Only invocation responses are limited: \
+                         messages received from the host are accepted regardless of their length.",
+                    ),
             )
     }
 
-    pub fn execute(&self, registry: Registry<'static>) -> Result<(), String> {
-        ctrlc::set_handler(|| {}).expect("failed setting SIGINT handler");
-
-        Worker::run(self.host, self.port, self.worker_id, registry);
-
-        Ok(())
+    pub fn execute(&self, registry: Registry<'static>, hooks: Hooks) -> Result<(), String> {
+        Worker::run(
+            self.host,
+            self.port,
+            self.worker_id,
+            self.max_message_length,
+            registry,
+            hooks,
+        )
     }
 }
 
@@ -68,6 +76,10 @@ impl<'a> From<&'a ArgMatches> for Run<'a> {
             worker_id: args
                 .value_of("worker_id")
                 .expect("A worker id is required."),
+            max_message_length: args.value_of("max_message_length").map(|len| {
+                len.parse::<usize>()
+                    .expect("Invalid maximum message length")
+            }),
         }
     }
 }
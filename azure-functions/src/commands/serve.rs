@@ -0,0 +1,49 @@
+use crate::{custom_handler::CustomHandler, hooks::Hooks, registry::Registry};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::env;
+
+// The environment variable the host uses to pass the port to listen on
+const PORT_ENV_VAR: &str = "FUNCTIONS_CUSTOMHANDLER_PORT";
+
+pub struct Serve<'a> {
+    pub port: Option<&'a str>,
+}
+
+impl<'a> Serve<'a> {
+    pub fn create_subcommand<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("serve")
+            .about("Runs the Azure Functions custom handler.")
+            .arg(
+                Arg::with_name("port")
+                    .long("port")
+                    .value_name("PORT")
+                    .help("The port to listen on for invocation requests. Defaults to the value of the FUNCTIONS_CUSTOMHANDLER_PORT environment variable."),
+            )
+    }
+
+    pub fn execute(&self, registry: Registry<'static>, hooks: Hooks) -> Result<(), String> {
+        let port = match self.port {
+            Some(port) => port.to_owned(),
+            None => env::var(PORT_ENV_VAR).map_err(|_| {
+                format!(
+                    "a port is required; use --port or set the {} environment variable.",
+                    PORT_ENV_VAR
+                )
+            })?,
+        };
+
+        let port = port
+            .parse::<u16>()
+            .map_err(|_| format!("invalid port number '{}'.", port))?;
+
+        CustomHandler::run(port, registry, hooks)
+    }
+}
+
+impl<'a> From<&'a ArgMatches> for Serve<'a> {
+    fn from(args: &'a ArgMatches) -> Self {
+        Serve {
+            port: args.value_of("port"),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{dev_server::DevServer, hooks::Hooks, registry::Registry};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+pub struct ServeHttp<'a> {
+    pub port: &'a str,
+    pub host_settings: Option<&'a str>,
+}
+
+impl<'a> ServeHttp<'a> {
+    pub fn create_subcommand<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("serve-http")
+            .about("Runs a standalone HTTP server for developing the Azure Functions application without the Azure Functions Host.")
+            .arg(
+                Arg::with_name("port")
+                    .long("port")
+                    .value_name("PORT")
+                    .help("The port to listen on for HTTP requests.")
+                    .default_value("8080"),
+            )
+            .arg(
+                Arg::with_name("host_settings")
+                    .long("host-settings")
+                    .value_name("HOST_SETTINGS_FILE")
+                    .help("The path to the host settings file to read the HTTP route prefix from. Defaults to the `host.json` file in the directory containing `Cargo.toml`, if present.")
+                    .validator(|v| {
+                        if Path::new(&v).is_file() {
+                            Ok(())
+                        } else {
+                            Err(format!("host settings file '{}' does not exist", v))
+                        }
+                    }),
+            )
+    }
+
+    pub fn execute(&self, registry: Registry<'static>, hooks: Hooks) -> Result<(), String> {
+        let port = self
+            .port
+            .parse::<u16>()
+            .map_err(|_| format!("invalid port number '{}'.", self.port))?;
+
+        let host_settings = match self.host_settings {
+            Some(path) => Some(PathBuf::from(path)),
+            None => env::var("CARGO_MANIFEST_DIR")
+                .map(|dir| PathBuf::from(dir).join("host.json"))
+                .ok()
+                .filter(|path| path.is_file()),
+        };
+
+        DevServer::run(port, host_settings.as_deref(), registry, hooks)
+    }
+}
+
+impl<'a> From<&'a ArgMatches> for ServeHttp<'a> {
+    fn from(args: &'a ArgMatches) -> Self {
+        ServeHttp {
+            port: args.value_of("port").expect("A port is required."),
+            host_settings: args.value_of("host_settings"),
+        }
+    }
+}
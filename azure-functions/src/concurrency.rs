@@ -0,0 +1,198 @@
+//! Module for limiting the number of concurrent invocations.
+use crate::codegen::Function;
+use lazy_static::lazy_static;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+// Represents an optional concurrency limit and the number of invocations waiting on it
+#[derive(Clone, Default)]
+struct Limiter {
+    semaphore: Option<Arc<Semaphore>>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl Limiter {
+    fn new(limit: Option<usize>) -> Self {
+        Limiter {
+            semaphore: limit.map(|l| Arc::new(Semaphore::new(l))),
+            queued: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    // Acquires a permit without waiting; returns None if the limit has been reached
+    fn try_acquire(&self) -> Option<Option<OwnedSemaphorePermit>> {
+        match &self.semaphore {
+            Some(semaphore) => semaphore.clone().try_acquire_owned().ok().map(Some),
+            None => Some(None),
+        }
+    }
+
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        match &self.semaphore {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore should not be closed"),
+            ),
+            None => None,
+        }
+    }
+}
+
+// Counts an invocation as queued until dropped
+struct Queued(Arc<AtomicUsize>);
+
+impl Queued {
+    fn new(queued: &Arc<AtomicUsize>) -> Self {
+        queued.fetch_add(1, Ordering::SeqCst);
+        Queued(queued.clone())
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Default)]
+struct Limits {
+    global: Limiter,
+    functions: HashMap<String, Limiter>,
+}
+
+lazy_static! {
+    static ref LIMITS: RwLock<Limits> = RwLock::new(Limits::default());
+}
+
+// Represents the permits held by a running invocation
+pub(crate) struct Permits {
+    _function: Option<OwnedSemaphorePermit>,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+// Sets the worker's concurrency limit and the limits of the given functions
+pub(crate) fn configure(max_concurrency: Option<usize>, functions: &[&Function]) {
+    *LIMITS.write().unwrap() = Limits {
+        global: Limiter::new(max_concurrency),
+        functions: functions
+            .iter()
+            .map(|f| (f.name.to_string(), Limiter::new(f.max_concurrency)))
+            .collect(),
+    };
+}
+
+// Waits until an invocation of the given function is within the concurrency limits
+//
+// The function's limit is acquired before the worker's limit so that invocations queued
+// for a busy function do not hold permits that other functions could use.
+pub(crate) async fn acquire(func: &Function) -> Permits {
+    let (function, global) = {
+        let limits = LIMITS.read().unwrap();
+        (
+            limits
+                .functions
+                .get(func.name.as_ref())
+                .cloned()
+                .unwrap_or_default(),
+            limits.global.clone(),
+        )
+    };
+
+    if let Some(function_permit) = function.try_acquire() {
+        if let Some(global_permit) = global.try_acquire() {
+            return Permits {
+                _function: function_permit,
+                _global: global_permit,
+            };
+        }
+    }
+
+    let _queued = (Queued::new(&function.queued), Queued::new(&global.queued));
+
+    let function_permit = function.acquire().await;
+    let global_permit = global.acquire().await;
+
+    Permits {
+        _function: function_permit,
+        _global: global_permit,
+    }
+}
+
+/// Gets the number of invocations queued by the worker because of concurrency limits.
+pub fn queue_depth() -> usize {
+    LIMITS.read().unwrap().global.queued.load(Ordering::SeqCst)
+}
+
+/// Gets the number of invocations of the given Azure Function queued by the worker
+/// because of concurrency limits.
+pub fn function_queue_depth(name: &str) -> usize {
+    LIMITS
+        .read()
+        .unwrap()
+        .functions
+        .get(name)
+        .map_or(0, |l| l.queued.load(Ordering::SeqCst))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use std::borrow::Cow;
+    use tokio::task::yield_now;
+
+    static LIMITED: Function = Function {
+        name: Cow::Borrowed("limited"),
+        disabled: false,
+        bindings: Cow::Borrowed(&[]),
+        invoker: None,
+        middleware: Cow::Borrowed(&[]),
+        max_concurrency: Some(1),
+        timeout: None,
+        manifest_dir: None,
+        file: None,
+    };
+
+    static UNLIMITED: Function = fixtures::function("unlimited", &[]);
+
+    #[tokio::test]
+    async fn it_queues_invocations_over_the_limits() {
+        configure(Some(2), &[&LIMITED, &UNLIMITED]);
+
+        let first = acquire(&LIMITED).await;
+        let queued = tokio::spawn(acquire(&LIMITED));
+        yield_now().await;
+
+        assert_eq!(function_queue_depth("limited"), 1);
+        assert_eq!(queue_depth(), 1);
+
+        // The queued invocation does not hold a permit of the worker's limit
+        let unlimited = acquire(&UNLIMITED).await;
+        let blocked = tokio::spawn(acquire(&UNLIMITED));
+        yield_now().await;
+
+        assert_eq!(function_queue_depth("unlimited"), 1);
+        assert_eq!(queue_depth(), 2);
+
+        drop(unlimited);
+        let _unlimited = blocked.await.unwrap();
+
+        assert_eq!(function_queue_depth("unlimited"), 0);
+        assert_eq!(queue_depth(), 1);
+
+        drop(first);
+        let _limited = queued.await.unwrap();
+
+        assert_eq!(function_queue_depth("limited"), 0);
+        assert_eq!(queue_depth(), 0);
+    }
+}
@@ -1,15 +1,29 @@
 //! Module for function invocation context.
-use std::{cell::RefCell, env, path::PathBuf};
+//!
+//! The invocation context is task-local: the worker sets it around every poll of the future of
+//! an invocation, so the context follows the invocation across `.await` points and threads.
+//! Work that runs elsewhere, such as a task spawned with
+//! `tokio::spawn`, a closure passed to `tokio::task::spawn_blocking` or a new thread, has no
+//! invocation context: `Context::current` returns None and its logs are not attributed to the
+//! invocation. Use `context::spawn`, `context::spawn_blocking` or `Context::scope` to carry
+//! the invocation context to such work.
+use crate::{
+    rpc::{InvocationRequest, RetryContext, RpcTraceContext},
+    worker::Capabilities,
+};
+use std::{collections::HashMap, env, path::PathBuf};
+
+mod cancellation;
+mod task;
+
+pub use self::cancellation::*;
+pub use self::task::*;
 
 pub(crate) const UNKNOWN_FUNCTION: &str = "<unknown>";
 
-thread_local!(pub(crate) static CURRENT: RefCell<Context> = RefCell::new(
-    Context{
-        invocation_id: String::new(),
-        function_id: String::new(),
-        function_name: UNKNOWN_FUNCTION
-    }
-));
+tokio::task_local! {
+    pub(crate) static CURRENT: Context;
+}
 
 /// Represents context about an Azure Function invocation.
 #[derive(Debug, Clone)]
@@ -17,56 +31,61 @@ pub struct Context {
     pub(crate) invocation_id: String,
     pub(crate) function_id: String,
     pub(crate) function_name: &'static str,
-}
-
-pub(crate) struct ContextGuard;
-
-impl Drop for ContextGuard {
-    fn drop(&mut self) {
-        Context::clear();
-    }
+    pub(crate) cancellation_token: CancellationToken,
+    pub(crate) trace_context: RpcTraceContext,
+    pub(crate) retry_context: Option<RetryContext>,
+    pub(crate) capabilities: Capabilities,
 }
 
 impl Context {
     /// Gets the current invocation context.
     ///
     /// Returns None if there is no invocation context.
+    ///
+    /// Tasks spawned with `tokio::spawn` do not have an invocation context;
+    /// use `context::spawn` or `context::spawn_blocking` to inherit the current context.
     pub fn current() -> Option<Self> {
-        let mut current = None;
-
-        CURRENT.with(|c| {
-            let c = c.borrow();
-            if !c.invocation_id.is_empty() {
-                current = Some(c.clone());
-            }
-        });
+        CURRENT.try_with(Context::clone).ok()
+    }
 
-        current
+    // Creates the context for the given invocation request.
+    pub(crate) fn new(
+        req: &InvocationRequest,
+        function_name: &'static str,
+        capabilities: Capabilities,
+    ) -> Self {
+        Context {
+            invocation_id: req.invocation_id.clone(),
+            function_id: req.function_id.clone(),
+            function_name,
+            cancellation_token: CancellationToken::new(),
+            trace_context: req.trace_context.clone().unwrap_or_default(),
+            retry_context: req.retry_context.clone(),
+            capabilities,
+        }
     }
 
-    #[must_use]
-    pub(crate) fn set(
+    #[cfg(test)]
+    pub(crate) fn test(
         invocation_id: &str,
         function_id: &str,
         function_name: &'static str,
-    ) -> ContextGuard {
-        CURRENT.with(|c| {
-            let mut c = c.borrow_mut();
-            c.invocation_id.replace_range(.., invocation_id);
-            c.function_id.replace_range(.., function_id);
-            c.function_name = function_name;
-        });
-
-        ContextGuard {}
+        cancellation_token: &CancellationToken,
+    ) -> Self {
+        Context {
+            invocation_id: invocation_id.to_owned(),
+            function_id: function_id.to_owned(),
+            function_name,
+            cancellation_token: cancellation_token.clone(),
+            trace_context: RpcTraceContext::default(),
+            retry_context: None,
+            capabilities: Capabilities::default(),
+        }
     }
 
-    pub(crate) fn clear() {
-        CURRENT.with(|c| {
-            let mut c = c.borrow_mut();
-            c.invocation_id.clear();
-            c.function_id.clear();
-            c.function_name = UNKNOWN_FUNCTION;
-        });
+    // Calls the given function with this as the current invocation context.
+    pub(crate) fn in_scope<R>(self, f: impl FnOnce() -> R) -> R {
+        CURRENT.sync_scope(self, f)
     }
 
     /// Gets the invocation identifier for the current Azure Function.
@@ -84,6 +103,102 @@ impl Context {
         self.function_name
     }
 
+    /// Gets the cancellation token for the current Azure Function invocation.
+    ///
+    /// The token is signalled when the Azure Functions Host cancels the invocation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::{
+    ///     bindings::{HttpRequest, HttpResponse},
+    ///     context::Context,
+    ///     func,
+    /// };
+    ///
+    /// #[func]
+    /// pub async fn aggregate(_req: HttpRequest) -> HttpResponse {
+    ///     let token = Context::current().unwrap().cancellation_token().clone();
+    ///
+    ///     let mut total = 0;
+    ///     for i in 0..1000 {
+    ///         if token.is_cancelled() {
+    ///             break;
+    ///         }
+    ///         total += i;
+    ///     }
+    ///
+    ///     format!("Total: {}", total).into()
+    /// }
+    /// ```
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    /// Gets the W3C trace context `traceparent` of the current Azure Function invocation.
+    ///
+    /// Returns None if the Azure Functions Host did not send a trace context.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::{
+    ///     bindings::{HttpRequest, HttpResponse},
+    ///     context::Context,
+    ///     func,
+    /// };
+    ///
+    /// #[func]
+    /// pub async fn proxy(_req: HttpRequest) -> HttpResponse {
+    ///     let context = Context::current().unwrap();
+    ///
+    ///     // Propagate the trace context to a downstream service
+    ///     let mut headers = Vec::new();
+    ///     if let Some(parent) = context.trace_parent() {
+    ///         headers.push(("traceparent", parent.to_owned()));
+    ///     }
+    ///     if let Some(state) = context.trace_state() {
+    ///         headers.push(("tracestate", state.to_owned()));
+    ///     }
+    ///
+    ///     format!("Propagating {} headers.", headers.len()).into()
+    /// }
+    /// ```
+    pub fn trace_parent(&self) -> Option<&str> {
+        Some(self.trace_context.trace_parent.as_str()).filter(|s| !s.is_empty())
+    }
+
+    /// Gets the W3C trace context `tracestate` of the current Azure Function invocation.
+    ///
+    /// Returns None if the Azure Functions Host did not send a trace state.
+    pub fn trace_state(&self) -> Option<&str> {
+        Some(self.trace_context.trace_state.as_str()).filter(|s| !s.is_empty())
+    }
+
+    /// Gets the trace attributes (e.g. the activity tags) sent by the Azure Functions Host.
+    pub fn trace_attributes(&self) -> &HashMap<String, String> {
+        &self.trace_context.attributes
+    }
+
+    /// Gets the number of times the current Azure Function invocation has been retried.
+    ///
+    /// Returns 0 for the first attempt or if the function has no retry policy.
+    pub fn retry_count(&self) -> u32 {
+        self.retry_context
+            .as_ref()
+            .map_or(0, |r| r.retry_count.max(0) as u32)
+    }
+
+    /// Gets the maximum number of retries of the current Azure Function invocation.
+    ///
+    /// Returns None if the function has no retry policy or retries indefinitely.
+    pub fn max_retry_count(&self) -> Option<u32> {
+        self.retry_context
+            .as_ref()
+            .filter(|r| r.max_retry_count >= 0)
+            .map(|r| r.max_retry_count as u32)
+    }
+
     /// Gets the directory for the current Azure Function.
     pub fn function_directory(&self) -> Option<PathBuf> {
         self.app_directory().map(|p| p.join(self.function_name))
@@ -109,12 +224,84 @@ mod tests {
 
     #[test]
     fn it_returns_current_context() {
-        let _guard = Context::set("1234", "5678", "foo");
-
-        let context = Context::current().unwrap();
+        let context = Context::test("1234", "5678", "foo", &CancellationToken::new())
+            .in_scope(|| Context::current().unwrap());
 
         assert_eq!(context.invocation_id(), "1234");
         assert_eq!(context.function_id(), "5678");
         assert_eq!(context.function_name(), "foo");
+        assert!(!context.cancellation_token().is_cancelled());
+    }
+
+    #[test]
+    fn it_returns_the_invocation_cancellation_token() {
+        let token = CancellationToken::new();
+
+        token.cancel();
+
+        assert!(
+            Context::test("1234", "5678", "foo", &token).in_scope(|| Context::current()
+                .unwrap()
+                .cancellation_token()
+                .is_cancelled())
+        );
+    }
+
+    #[test]
+    fn it_returns_the_trace_and_retry_context() {
+        let req = InvocationRequest {
+            invocation_id: "1234".to_owned(),
+            function_id: "5678".to_owned(),
+            trace_context: Some(RpcTraceContext {
+                trace_parent: "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".to_owned(),
+                trace_state: "congo=t61rcWkgMzE".to_owned(),
+                attributes: vec![("ProcessId".to_owned(), "42".to_owned())]
+                    .into_iter()
+                    .collect(),
+            }),
+            retry_context: Some(RetryContext {
+                retry_count: 2,
+                max_retry_count: 5,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let context = Context::new(&req, "foo", Capabilities::default())
+            .in_scope(|| Context::current().unwrap());
+
+        assert_eq!(context.invocation_id(), "1234");
+        assert_eq!(
+            context.trace_parent(),
+            Some("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01")
+        );
+        assert_eq!(context.trace_state(), Some("congo=t61rcWkgMzE"));
+        assert_eq!(context.trace_attributes().get("ProcessId").unwrap(), "42");
+        assert_eq!(context.retry_count(), 2);
+        assert_eq!(context.max_retry_count(), Some(5));
+    }
+
+    #[test]
+    fn it_returns_no_trace_or_retry_context_by_default() {
+        let context = Context::test("1234", "5678", "foo", &CancellationToken::new())
+            .in_scope(|| Context::current().unwrap());
+
+        assert_eq!(context.trace_parent(), None);
+        assert_eq!(context.trace_state(), None);
+        assert_eq!(context.retry_count(), 0);
+        assert_eq!(context.max_retry_count(), None);
+    }
+
+    #[test]
+    fn it_restores_the_previous_context() {
+        Context::test("1234", "5678", "foo", &CancellationToken::new()).in_scope(|| {
+            Context::test("4321", "8765", "bar", &CancellationToken::new()).in_scope(|| {
+                assert_eq!(Context::current().unwrap().invocation_id(), "4321");
+            });
+
+            assert_eq!(Context::current().unwrap().invocation_id(), "1234");
+        });
+
+        assert!(Context::current().is_none());
     }
 }
@@ -0,0 +1,133 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Debug, Default)]
+struct State {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+/// Represents a token that is signalled when the Azure Functions Host cancels an invocation.
+///
+/// Long-running functions should periodically check `is_cancelled` or await `cancelled`
+/// and stop work when the token is signalled.
+///
+/// If the function does not complete before the grace period specified by the host expires,
+/// the invocation's future is dropped and a cancelled result is returned to the host.
+/// Synchronous functions cannot be interrupted; their eventual result is discarded.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<State>);
+
+impl CancellationToken {
+    pub(crate) fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Determines if cancellation of the invocation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Gets a future that completes when cancellation of the invocation is requested.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azure_functions::context::CancellationToken;
+    /// use futures::future::{select, Either, FutureExt};
+    ///
+    /// async fn aggregate(token: CancellationToken) -> Option<u32> {
+    ///     let work = async { 42 };
+    ///
+    ///     match select(work.boxed(), token.cancelled()).await {
+    ///         Either::Left((result, _)) => Some(result),
+    ///         Either::Right(_) => None,
+    ///     }
+    /// }
+    /// ```
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled(self.clone())
+    }
+
+    pub(crate) fn cancel(&self) {
+        if self.0.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        for waker in self.0.wakers.lock().unwrap().drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// Represents a future that completes when an invocation is cancelled.
+#[derive(Debug)]
+pub struct Cancelled(CancellationToken);
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if self.0.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = (self.0).0.wakers.lock().unwrap();
+
+        // Check again while holding the lock to avoid missing a concurrent cancellation
+        if self.0.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, future::FutureExt};
+
+    #[test]
+    fn it_is_not_cancelled_by_default() {
+        let token = CancellationToken::new();
+
+        assert!(!token.is_cancelled());
+        assert!(token.cancelled().now_or_never().is_none());
+    }
+
+    #[test]
+    fn it_cancels() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+
+        token.cancel();
+
+        assert!(token.is_cancelled());
+        assert!(clone.is_cancelled());
+        block_on(clone.cancelled());
+    }
+
+    #[test]
+    fn it_wakes_pending_futures() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+
+        let thread = std::thread::spawn(move || block_on(clone.cancelled()));
+
+        token.cancel();
+
+        thread.join().unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+use crate::context::Context;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+use tokio::task::JoinHandle;
+use tracing::{Instrument, Span};
+
+/// Represents a future that is polled with an invocation context.
+///
+/// `Context::current` returns the invocation context while the future is polled,
+/// which also attributes any logs written by the future to the invocation.
+#[derive(Debug)]
+pub struct WithContext<F> {
+    inner: Pin<Box<F>>,
+    context: Option<Context>,
+}
+
+impl<F: Future> Future for WithContext<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        match this.context.clone() {
+            Some(context) => context.in_scope(|| this.inner.as_mut().poll(cx)),
+            None => this.inner.as_mut().poll(cx),
+        }
+    }
+}
+
+impl Context {
+    /// Wraps the given future so that it is polled with this invocation context.
+    pub fn scope<F: Future>(self, future: F) -> WithContext<F> {
+        WithContext {
+            inner: Box::pin(future),
+            context: Some(self),
+        }
+    }
+}
+
+/// Spawns a new task that inherits the current invocation context.
+///
+/// Use this in place of `tokio::spawn` so that logs from the spawned task are attributed
+/// to the invocation that spawned it; a task spawned with `tokio::spawn` has no invocation
+/// context.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::{
+///     bindings::{HttpRequest, HttpResponse},
+///     context, func,
+/// };
+/// use log::info;
+///
+/// #[func]
+/// pub async fn fan_out(_req: HttpRequest) -> HttpResponse {
+///     let tasks: Vec<_> = (0..10)
+///         .map(|i| {
+///             context::spawn(async move {
+///                 info!("Processing item {}.", i);
+///                 i * 2
+///             })
+///         })
+///         .collect();
+///
+///     let mut total = 0;
+///     for task in tasks {
+///         total += task.await.unwrap();
+///     }
+///
+///     format!("Total: {}", total).into()
+/// }
+/// ```
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(
+        WithContext {
+            inner: Box::pin(future),
+            context: Context::current(),
+        }
+        .instrument(Span::current()),
+    )
+}
+
+/// Runs the given blocking function on a thread where blocking is acceptable,
+/// inheriting the current invocation context.
+///
+/// Use this in place of `tokio::task::spawn_blocking` so that logs from the function are
+/// attributed to the invocation that spawned it.
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let context = Context::current();
+    let span = Span::current();
+
+    tokio::task::spawn_blocking(move || match context {
+        Some(context) => context.in_scope(|| span.in_scope(f)),
+        None => span.in_scope(f),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::CancellationToken;
+    use futures::executor::block_on;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn it_polls_with_the_context() {
+        let context = Context::test("1234", "5678", "foo", &CancellationToken::new());
+
+        assert!(Context::current().is_none());
+
+        let invocation_id = block_on(
+            context.scope(async { Context::current().map(|c| c.invocation_id().to_owned()) }),
+        );
+
+        assert_eq!(invocation_id.as_deref(), Some("1234"));
+        assert!(Context::current().is_none());
+    }
+
+    #[tokio::test]
+    async fn it_only_spawns_tasks_with_the_context_through_context_spawn() {
+        let context = Context::test("1234", "5678", "foo", &CancellationToken::new());
+
+        let (inherited, lost) = context
+            .scope(async {
+                (
+                    spawn(async { Context::current().is_some() }).await.unwrap(),
+                    tokio::spawn(async { Context::current().is_some() })
+                        .await
+                        .unwrap(),
+                )
+            })
+            .await;
+
+        assert!(inherited);
+        assert!(!lost);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn it_keeps_the_context_when_the_task_moves_to_another_thread() {
+        let context = Context::test("1234", "5678", "foo", &CancellationToken::new());
+
+        let (before, after, invocation_id) = tokio::spawn(context.scope(async {
+            let before = thread::current().id();
+
+            // Hand the scheduler of this worker thread over to another thread so that the
+            // task resumes on a different thread after the next await
+            tokio::task::block_in_place(|| thread::sleep(Duration::from_millis(100)));
+            tokio::task::yield_now().await;
+
+            (
+                before,
+                thread::current().id(),
+                Context::current().map(|c| c.invocation_id().to_owned()),
+            )
+        }))
+        .await
+        .unwrap();
+
+        assert_ne!(before, after);
+        assert_eq!(invocation_id.as_deref(), Some("1234"));
+    }
+}
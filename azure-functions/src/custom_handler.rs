@@ -0,0 +1,462 @@
+use crate::{
+    codegen::{bindings::Binding, Function},
+    dispatcher::Dispatcher,
+    hooks::Hooks,
+    registry::Registry,
+    rpc::{
+        status_result::Status, typed_data::Data, InvocationRequest, InvocationResponse,
+        ParameterBinding, RpcHttp, TypedData,
+    },
+};
+use futures::channel::oneshot;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde::Deserialize;
+use serde_json::{from_slice, from_str, json, Map, Value};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+// The header the host uses to send the invocation identifier of a request
+const INVOCATION_ID_HEADER: &str = "x-azure-functions-invocationid";
+// The metadata key of the system properties of an invocation, which are not trigger metadata
+const SYSTEM_METADATA_KEY: &str = "sys";
+
+// Represents an invocation request from the host
+#[derive(Deserialize)]
+struct HandlerRequest {
+    #[serde(rename = "Data", default)]
+    data: Map<String, Value>,
+    #[serde(rename = "Metadata", default)]
+    metadata: Map<String, Value>,
+}
+
+// Represents the data of a HTTP trigger in an invocation request
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct HttpData {
+    url: String,
+    method: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, Vec<String>>,
+    params: HashMap<String, String>,
+    body: Option<Value>,
+}
+
+#[derive(Clone)]
+struct Handler {
+    registry: Arc<Registry<'static>>,
+    dispatcher: Dispatcher,
+}
+
+/// Implements the Azure Functions custom handler protocol.
+///
+/// The host sends a POST request to `/{function}` for every invocation with the input binding
+/// data and trigger metadata; the response contains the output bindings, the return value and
+/// the logs of the invocation.
+pub struct CustomHandler;
+
+impl CustomHandler {
+    pub fn run(port: u16, mut registry: Registry<'static>, hooks: Hooks) -> Result<(), String> {
+        // Functions are identified by name in the custom handler protocol
+        let names: Vec<String> = registry.iter().map(|(name, _)| name.clone()).collect();
+        for name in names {
+            registry.register(&name, &name);
+        }
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let shutdown = shutdown_signal();
+
+        runtime.block_on(async move {
+            let handler = Handler {
+                registry: Arc::new(registry),
+                dispatcher: Dispatcher::start(),
+            };
+
+            hooks
+                .start()
+                .await
+                .map_err(|e| format!("startup hook failed: {}", e))?;
+
+            let addr = SocketAddr::from(([127, 0, 0, 1], port));
+            let service = make_service_fn(move |_| {
+                let handler = handler.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| handler.clone().handle(req)))
+                }
+            });
+
+            let server = Server::bind(&addr).serve(service);
+
+            println!("Azure Functions custom handler listening on {}.", addr);
+
+            server
+                .with_graceful_shutdown(async move {
+                    shutdown.await.unwrap_or(());
+                })
+                .await
+                .unwrap_or_else(|e| panic!("failed to run custom handler: {}", e));
+
+            hooks.shutdown().await;
+
+            Ok(())
+        })
+    }
+}
+
+// Creates a future that completes when the process is interrupted
+pub(crate) fn shutdown_signal() -> oneshot::Receiver<()> {
+    let (sender, receiver) = oneshot::channel::<()>();
+    let sender = Mutex::new(Some(sender));
+
+    ctrlc::set_handler(move || {
+        if let Some(sender) = sender.lock().unwrap().take() {
+            sender.send(()).unwrap_or(());
+        }
+    })
+    .expect("failed setting SIGINT handler");
+
+    receiver
+}
+
+impl Handler {
+    async fn handle(self, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let name = req.uri().path().trim_matches('/').to_owned();
+
+        let func = match self.registry.get(&name) {
+            Some(func) => func,
+            None => {
+                return Ok(error_response(
+                    StatusCode::NOT_FOUND,
+                    format!("Azure Function '{}' does not exist.", name),
+                ))
+            }
+        };
+
+        if req.method() != Method::POST {
+            return Ok(error_response(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "Invocation requests must use the POST method.".to_owned(),
+            ));
+        }
+
+        let invocation_id = req
+            .headers()
+            .get(INVOCATION_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| self.dispatcher.next_invocation_id());
+
+        let body = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(body) => body,
+            Err(e) => {
+                return Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("failed to read invocation request: {}", e),
+                ))
+            }
+        };
+
+        let request: HandlerRequest = match from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                return Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("invalid invocation request: {}", e),
+                ))
+            }
+        };
+
+        let (res, logs) = self
+            .dispatcher
+            .invoke(func, to_invocation_request(func, invocation_id, request))
+            .await;
+
+        Ok(to_response(res, logs))
+    }
+}
+
+fn to_invocation_request(
+    func: &'static Function,
+    invocation_id: String,
+    req: HandlerRequest,
+) -> InvocationRequest {
+    let input_data = req
+        .data
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(name, value)| {
+            let is_http = func.bindings.iter().any(|b| match b {
+                Binding::HttpTrigger(t) => t.name == name,
+                _ => false,
+            });
+
+            let data = if is_http {
+                to_http_data(value)
+            } else {
+                to_typed_data(value)
+            };
+
+            ParameterBinding::new(name, data)
+        })
+        .collect();
+
+    let trigger_metadata = req
+        .metadata
+        .into_iter()
+        .filter(|(name, _)| name != SYSTEM_METADATA_KEY)
+        .map(|(name, value)| (name, to_typed_data(value)))
+        .collect();
+
+    InvocationRequest {
+        invocation_id,
+        function_id: func.name.to_string(),
+        input_data,
+        trigger_metadata,
+        ..Default::default()
+    }
+}
+
+pub(crate) fn to_typed_data(value: Value) -> TypedData {
+    TypedData {
+        data: Some(match value {
+            Value::String(s) => Data::String(s),
+            v => Data::Json(v.to_string()),
+        }),
+    }
+}
+
+fn to_http_data(value: Value) -> TypedData {
+    let http: HttpData = serde_json::from_value(value).unwrap_or_default();
+
+    TypedData {
+        data: Some(Data::Http(Box::new(RpcHttp {
+            method: http.method,
+            url: http.url,
+            headers: http
+                .headers
+                .into_iter()
+                .map(|(k, v)| (k.to_lowercase(), v.join(", ")))
+                .collect(),
+            body: http.body.map(|b| Box::new(to_typed_data(b))),
+            params: http.params,
+            query: http.query,
+            ..Default::default()
+        }))),
+    }
+}
+
+// Converts output data to the value sent to the host
+//
+// Bytes are sent as a string when they are valid UTF-8 and are base64-encoded otherwise.
+pub(crate) fn to_value(data: TypedData) -> Value {
+    match data.data {
+        Some(Data::String(s)) => Value::String(s),
+        Some(Data::Json(s)) => from_str(&s).unwrap_or(Value::String(s)),
+        Some(Data::Bytes(b)) | Some(Data::Stream(b)) => match String::from_utf8(b) {
+            Ok(s) => Value::String(s),
+            Err(e) => Value::String(base64::encode(e.as_bytes())),
+        },
+        Some(Data::Int(i)) => Value::from(i),
+        Some(Data::Double(d)) => Value::from(d),
+        Some(Data::Http(http)) => json!({
+            "statusCode": http.status_code,
+            "headers": http.headers,
+            "body": http.body.map(|b| match to_value(*b) {
+                Value::String(s) => Value::String(s),
+                v => Value::String(v.to_string()),
+            }),
+        }),
+        _ => Value::Null,
+    }
+}
+
+pub(crate) fn to_response(res: InvocationResponse, mut logs: Vec<String>) -> Response<Body> {
+    let result = res.result.unwrap_or_default();
+
+    let status = if result.status == Status::Success as i32 {
+        StatusCode::OK
+    } else {
+        // The host reports the invocation as failed for a non-success status code
+        logs.push(result.result);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let outputs: Map<String, Value> = res
+        .output_data
+        .into_iter()
+        .map(|b| {
+            (
+                b.name.clone(),
+                b.into_data().map(to_value).unwrap_or(Value::Null),
+            )
+        })
+        .collect();
+
+    json_response(
+        status,
+        json!({
+            "Outputs": outputs,
+            "Logs": logs,
+            "ReturnValue": res.return_value.map(to_value),
+        }),
+    )
+}
+
+pub(crate) fn error_response(status: StatusCode, message: String) -> Response<Body> {
+    json_response(
+        status,
+        json!({
+            "Outputs": {},
+            "Logs": [message],
+            "ReturnValue": null,
+        }),
+    )
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codegen::bindings::{Http, HttpTrigger},
+        fixtures,
+    };
+    use std::borrow::Cow;
+
+    static HELLO_FUNCTION: Function = fixtures::function(
+        "hello",
+        &[
+            Binding::HttpTrigger(HttpTrigger {
+                name: Cow::Borrowed("req"),
+                auth_level: None,
+                methods: Cow::Borrowed(&[]),
+                route: None,
+            }),
+            Binding::Http(Http {
+                name: Cow::Borrowed("$return"),
+            }),
+        ],
+    );
+
+    #[test]
+    fn it_converts_an_invocation_request() {
+        let req: HandlerRequest = serde_json::from_value(json!({
+            "Data": {
+                "req": {
+                    "Url": "http://localhost:7071/api/hello?name=Peter",
+                    "Method": "GET",
+                    "Query": { "name": "Peter" },
+                    "Headers": { "Accept": ["text/plain", "text/html"] },
+                    "Params": {},
+                    "Body": "hello"
+                },
+                "message": { "text": "hi" }
+            },
+            "Metadata": {
+                "DequeueCount": 1,
+                "Id": "1234",
+                "sys": { "MethodName": "hello" }
+            }
+        }))
+        .unwrap();
+
+        let req = to_invocation_request(&HELLO_FUNCTION, "1".to_owned(), req);
+
+        assert_eq!(req.invocation_id, "1");
+        assert_eq!(req.function_id, "hello");
+        assert_eq!(req.input_data.len(), 2);
+
+        let http = req
+            .input_data
+            .iter()
+            .find(|b| b.name == "req")
+            .and_then(|b| match b.data().unwrap().data.as_ref() {
+                Some(Data::Http(http)) => Some(http),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(http.method, "GET");
+        assert_eq!(http.url, "http://localhost:7071/api/hello?name=Peter");
+        assert_eq!(http.query.get("name").unwrap(), "Peter");
+        assert_eq!(http.headers.get("accept").unwrap(), "text/plain, text/html");
+        assert_eq!(
+            http.body.as_ref().unwrap().data,
+            Some(Data::String("hello".to_owned()))
+        );
+
+        let message = req.input_data.iter().find(|b| b.name == "message").unwrap();
+        assert_eq!(
+            message.data().unwrap().data,
+            Some(Data::Json(r#"{"text":"hi"}"#.to_owned()))
+        );
+
+        assert_eq!(req.trigger_metadata.len(), 2);
+        assert_eq!(
+            req.trigger_metadata["DequeueCount"].data,
+            Some(Data::Json("1".to_owned()))
+        );
+        assert_eq!(
+            req.trigger_metadata["Id"].data,
+            Some(Data::String("1234".to_owned()))
+        );
+    }
+
+    #[test]
+    fn it_converts_output_data() {
+        let mut headers = HashMap::new();
+        headers.insert("content-type".to_owned(), "text/plain".to_owned());
+
+        let value = to_value(TypedData {
+            data: Some(Data::Http(Box::new(RpcHttp {
+                status_code: "201".to_owned(),
+                headers,
+                body: Some(Box::new(TypedData {
+                    data: Some(Data::String("created".to_owned())),
+                })),
+                ..Default::default()
+            }))),
+        });
+
+        assert_eq!(
+            value,
+            json!({
+                "statusCode": "201",
+                "headers": { "content-type": "text/plain" },
+                "body": "created"
+            })
+        );
+
+        assert_eq!(
+            to_value(TypedData {
+                data: Some(Data::Json(r#"["a","b"]"#.to_owned())),
+            }),
+            json!(["a", "b"])
+        );
+        assert_eq!(
+            to_value(TypedData {
+                data: Some(Data::Bytes(b"bytes".to_vec())),
+            }),
+            json!("bytes")
+        );
+        assert_eq!(
+            to_value(TypedData {
+                data: Some(Data::Stream(vec![0xFF, 0xFE, 0x00])),
+            }),
+            json!("//4A")
+        );
+    }
+}
@@ -0,0 +1,738 @@
+use crate::{
+    codegen::{bindings::Binding, Function},
+    custom_handler::{error_response, shutdown_signal, to_response, to_typed_data},
+    dispatcher::Dispatcher,
+    hooks::Hooks,
+    metrics,
+    registry::Registry,
+    rpc::{
+        status_result::Status, typed_data::Data, InvocationRequest, InvocationResponse,
+        ParameterBinding, RpcHttp, TypedData,
+    },
+};
+use hyper::{
+    header::HeaderValue,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+use serde_json::{from_slice, Value};
+use std::{
+    cmp::Reverse, collections::HashMap, convert::Infallible, fs, net::SocketAddr, path::Path,
+    sync::Arc,
+};
+
+// The default route prefix of HTTP-triggered functions
+const DEFAULT_ROUTE_PREFIX: &str = "api";
+// The path of the endpoint used to invoke functions that are not HTTP-triggered
+const ADMIN_PATH: &str = "admin/functions";
+// The path of the endpoint that serves worker metrics in the Prometheus text format
+const METRICS_PATH: &str = "admin/metrics";
+
+// Represents a segment of a route template
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Parameter {
+        name: String,
+        constraint: Option<String>,
+        optional: bool,
+        catch_all: bool,
+    },
+}
+
+impl Segment {
+    fn parse(segment: &str) -> Segment {
+        if !segment.starts_with('{') || !segment.ends_with('}') {
+            return Segment::Literal(segment.to_lowercase());
+        }
+
+        let mut name = &segment[1..segment.len() - 1];
+
+        let catch_all = name.starts_with('*');
+        if catch_all {
+            name = &name[1..];
+        }
+
+        let optional = name.ends_with('?');
+        if optional {
+            name = &name[..name.len() - 1];
+        }
+
+        let mut parts = name.splitn(2, ':');
+        let name = parts.next().unwrap().to_owned();
+        let constraint = parts.next().map(str::to_lowercase);
+
+        Segment::Parameter {
+            name,
+            constraint,
+            optional,
+            catch_all,
+        }
+    }
+}
+
+// Represents the route of a HTTP-triggered function
+struct Route {
+    template: String,
+    segments: Vec<Segment>,
+    methods: Vec<String>,
+    binding: String,
+    func: &'static Function,
+}
+
+impl Route {
+    fn new(func: &'static Function) -> Option<Route> {
+        let trigger = func.bindings.iter().find_map(|b| match b {
+            Binding::HttpTrigger(t) => Some(t),
+            _ => None,
+        })?;
+
+        let template = trigger.route.as_ref().unwrap_or(&func.name);
+
+        Some(Route {
+            template: template.trim_matches('/').to_owned(),
+            segments: template
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(Segment::parse)
+                .collect(),
+            methods: trigger.methods.iter().map(|m| m.to_uppercase()).collect(),
+            binding: trigger.name.to_string(),
+            func,
+        })
+    }
+
+    fn allows(&self, method: &Method) -> bool {
+        self.methods.is_empty() || self.methods.iter().any(|m| m == method.as_str())
+    }
+
+    // Matches the given path segments, returning the route parameters upon success
+    fn matches(&self, path: &[&str]) -> Option<HashMap<String, String>> {
+        let mut params = HashMap::new();
+        let mut path = path.iter();
+
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Literal(literal) => {
+                    if path.next()?.to_lowercase() != *literal {
+                        return None;
+                    }
+                }
+                Segment::Parameter {
+                    name,
+                    catch_all: true,
+                    ..
+                } => {
+                    let rest: Vec<&str> = path.by_ref().copied().collect();
+                    if !rest.is_empty() {
+                        params.insert(name.clone(), rest.join("/"));
+                    }
+                }
+                Segment::Parameter {
+                    name,
+                    constraint,
+                    optional,
+                    ..
+                } => match path.next() {
+                    Some(value) => {
+                        if !satisfies(constraint.as_ref().map(String::as_str), value) {
+                            return None;
+                        }
+                        params.insert(name.clone(), (*value).to_owned());
+                    }
+                    None if *optional => {}
+                    None => return None,
+                },
+            }
+        }
+
+        if path.next().is_some() {
+            return None;
+        }
+
+        Some(params)
+    }
+}
+
+// Gets the route prefix of HTTP-triggered functions from the host settings
+fn route_prefix(host_settings: &Value) -> String {
+    host_settings
+        .pointer("/extensions/http/routePrefix")
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_ROUTE_PREFIX)
+        .trim_matches('/')
+        .to_owned()
+}
+
+// Splits a request path into percent-decoded segments, without the route prefix
+//
+// Returns None if the path does not start with the route prefix.
+fn route_segments(path: &str, prefix: &str) -> Option<Vec<String>> {
+    let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(decode)
+        .collect();
+    let prefix: Vec<String> = prefix
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(decode)
+        .collect();
+
+    if segments.len() < prefix.len()
+        || !segments
+            .iter()
+            .zip(prefix.iter())
+            .all(|(s, p)| s.eq_ignore_ascii_case(p))
+    {
+        return None;
+    }
+
+    Some(segments[prefix.len()..].to_vec())
+}
+
+// Checks a route parameter value against the common route constraints
+fn satisfies(constraint: Option<&str>, value: &str) -> bool {
+    match constraint {
+        Some("int") | Some("long") => value.parse::<i64>().is_ok(),
+        Some("float") | Some("double") | Some("decimal") => value.parse::<f64>().is_ok(),
+        Some("bool") => value.parse::<bool>().is_ok(),
+        Some("alpha") => value.chars().all(char::is_alphabetic),
+        _ => true,
+    }
+}
+
+// Represents a request to the admin endpoint
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AdminRequest {
+    input: Value,
+    metadata: HashMap<String, Value>,
+}
+
+#[derive(Clone)]
+struct Handler {
+    registry: Arc<Registry<'static>>,
+    routes: Arc<Vec<Route>>,
+    route_prefix: Arc<String>,
+    dispatcher: Dispatcher,
+}
+
+/// Implements a standalone HTTP server for developing Azure Functions.
+///
+/// HTTP-triggered functions are invoked directly for requests matching their routes under the
+/// route prefix, without an Azure Functions Host. The route prefix is read from
+/// `extensions.http.routePrefix` in the given host settings file and defaults to `api`.
+/// Other functions are invoked by posting `{"input": ...}` to `/admin/functions/{function}`.
+pub struct DevServer;
+
+impl DevServer {
+    pub fn run(
+        port: u16,
+        host_settings: Option<&Path>,
+        mut registry: Registry<'static>,
+        hooks: Hooks,
+    ) -> Result<(), String> {
+        let route_prefix =
+            match host_settings {
+                Some(path) => {
+                    let settings = fs::read(path).map_err(|e| {
+                        format!("failed to read host settings '{}': {}", path.display(), e)
+                    })?;
+                    route_prefix(&from_slice(&settings).map_err(|e| {
+                        format!("invalid host settings '{}': {}", path.display(), e)
+                    })?)
+                }
+                None => DEFAULT_ROUTE_PREFIX.to_owned(),
+            };
+
+        let functions: Vec<&'static Function> = registry.iter().map(|(_, f)| *f).collect();
+        for func in functions.iter() {
+            registry.register(&func.name, &func.name);
+        }
+
+        let mut routes: Vec<Route> = functions
+            .iter()
+            .filter(|f| !f.disabled)
+            .filter_map(|f| Route::new(f))
+            .collect();
+
+        // Prefer routes with more literal segments when a request matches multiple routes
+        routes.sort_by_key(|r| {
+            Reverse(
+                r.segments
+                    .iter()
+                    .filter(|s| matches!(s, Segment::Literal(_)))
+                    .count(),
+            )
+        });
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let shutdown = shutdown_signal();
+
+        runtime.block_on(async move {
+            let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+            for route in routes.iter() {
+                let path: Vec<&str> = [route_prefix.as_str(), route.template.as_str()]
+                    .iter()
+                    .copied()
+                    .filter(|s| !s.is_empty())
+                    .collect();
+
+                println!(
+                    "{}: [{}] http://{}/{}",
+                    route.func.name,
+                    if route.methods.is_empty() {
+                        "ALL".to_owned()
+                    } else {
+                        route.methods.join(",")
+                    },
+                    addr,
+                    path.join("/")
+                );
+            }
+
+            let handler = Handler {
+                registry: Arc::new(registry),
+                routes: Arc::new(routes),
+                route_prefix: Arc::new(route_prefix),
+                dispatcher: Dispatcher::start(),
+            };
+
+            hooks
+                .start()
+                .await
+                .map_err(|e| format!("startup hook failed: {}", e))?;
+
+            let service = make_service_fn(move |_| {
+                let handler = handler.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| handler.clone().handle(req)))
+                }
+            });
+
+            println!("Azure Functions development server listening on {}.", addr);
+
+            Server::bind(&addr)
+                .serve(service)
+                .with_graceful_shutdown(async move {
+                    shutdown.await.unwrap_or(());
+                })
+                .await
+                .unwrap_or_else(|e| panic!("failed to run development server: {}", e));
+
+            hooks.shutdown().await;
+
+            Ok(())
+        })
+    }
+}
+
+impl Handler {
+    async fn handle(self, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let path = req.uri().path().trim_matches('/').to_owned();
+
+        if path == METRICS_PATH && req.method() == Method::GET {
+            return Ok(Response::builder()
+                .header("Content-Type", "text/plain; version=0.0.4")
+                .body(Body::from(metrics::to_prometheus()))
+                .unwrap());
+        }
+
+        if let Some(name) = path.strip_prefix(ADMIN_PATH) {
+            return Ok(self.handle_admin(name.trim_start_matches('/'), req).await);
+        }
+
+        let segments = match route_segments(&path, &self.route_prefix) {
+            Some(segments) => segments,
+            None => return Ok(not_found()),
+        };
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        let mut allowed = false;
+        for route in self.routes.iter() {
+            let params = match route.matches(&segments) {
+                Some(params) => params,
+                None => continue,
+            };
+
+            if !route.allows(req.method()) {
+                allowed = true;
+                continue;
+            }
+
+            return Ok(self.handle_http(route, params, req).await);
+        }
+
+        Ok(if allowed {
+            Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .body(Body::empty())
+                .unwrap()
+        } else {
+            not_found()
+        })
+    }
+
+    async fn handle_http(
+        &self,
+        route: &Route,
+        params: HashMap<String, String>,
+        req: Request<Body>,
+    ) -> Response<Body> {
+        let (parts, body) = req.into_parts();
+
+        let body = match hyper::body::to_bytes(body).await {
+            Ok(body) => body,
+            Err(e) => {
+                return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from(format!("failed to read request body: {}", e)))
+                    .unwrap()
+            }
+        };
+
+        let mut headers: HashMap<String, String> = HashMap::new();
+        for (name, value) in parts.headers.iter() {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            headers
+                .entry(name.as_str().to_owned())
+                .and_modify(|v| {
+                    v.push_str(", ");
+                    v.push_str(&value);
+                })
+                .or_insert_with(|| value.into_owned());
+        }
+
+        let url = format!(
+            "http://{}{}",
+            headers.get("host").map_or("localhost", String::as_str),
+            parts.uri.path_and_query().map_or("/", |p| p.as_str())
+        );
+
+        let query = parts
+            .uri
+            .query()
+            .map(|q| form_urlencoded::parse(q.as_bytes()).into_owned().collect())
+            .unwrap_or_default();
+
+        let body = if body.is_empty() {
+            None
+        } else {
+            Some(Box::new(TypedData {
+                data: Some(match String::from_utf8(body.to_vec()) {
+                    Ok(s) => Data::String(s),
+                    Err(e) => Data::Bytes(e.into_bytes()),
+                }),
+            }))
+        };
+
+        let trigger_metadata = params
+            .iter()
+            .map(|(name, value)| (name.clone(), to_typed_data(Value::String(value.clone()))))
+            .collect();
+
+        let req = InvocationRequest {
+            invocation_id: self.dispatcher.next_invocation_id(),
+            function_id: route.func.name.to_string(),
+            input_data: vec![ParameterBinding::new(
+                route.binding.clone(),
+                TypedData {
+                    data: Some(Data::Http(Box::new(RpcHttp {
+                        method: parts.method.as_str().to_owned(),
+                        url,
+                        headers,
+                        body,
+                        params,
+                        query,
+                        ..Default::default()
+                    }))),
+                },
+            )],
+            trigger_metadata,
+            ..Default::default()
+        };
+
+        let (res, logs) = self.dispatcher.invoke(route.func, req).await;
+
+        for log in logs {
+            println!("[{}] {}", route.func.name, log);
+        }
+
+        to_http_response(res)
+    }
+
+    async fn handle_admin(&self, name: &str, req: Request<Body>) -> Response<Body> {
+        let func = match self.registry.get(name) {
+            Some(func) => func,
+            None => {
+                return error_response(
+                    StatusCode::NOT_FOUND,
+                    format!("Azure Function '{}' does not exist.", name),
+                )
+            }
+        };
+
+        if req.method() != Method::POST {
+            return error_response(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "Invocation requests must use the POST method.".to_owned(),
+            );
+        }
+
+        let trigger = match func.bindings.iter().find(|b| b.is_trigger()) {
+            Some(trigger) => trigger.name().unwrap_or_default().to_owned(),
+            None => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Azure Function '{}' does not have a trigger.", name),
+                )
+            }
+        };
+
+        let body = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(body) => body,
+            Err(e) => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("failed to read invocation request: {}", e),
+                )
+            }
+        };
+
+        let request: AdminRequest = if body.is_empty() {
+            AdminRequest::default()
+        } else {
+            match from_slice(&body) {
+                Ok(request) => request,
+                Err(e) => {
+                    return error_response(
+                        StatusCode::BAD_REQUEST,
+                        format!("invalid invocation request: {}", e),
+                    )
+                }
+            }
+        };
+
+        let req = InvocationRequest {
+            invocation_id: self.dispatcher.next_invocation_id(),
+            function_id: func.name.to_string(),
+            input_data: vec![ParameterBinding::new(trigger, to_typed_data(request.input))],
+            trigger_metadata: request
+                .metadata
+                .into_iter()
+                .map(|(name, value)| (name, to_typed_data(value)))
+                .collect(),
+            ..Default::default()
+        };
+
+        let (res, logs) = self.dispatcher.invoke(func, req).await;
+
+        for log in logs.iter() {
+            println!("[{}] {}", func.name, log);
+        }
+
+        to_response(res, logs)
+    }
+}
+
+fn not_found() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::empty())
+        .unwrap()
+}
+
+// Converts the HTTP output of an invocation to a response
+fn to_http_response(res: InvocationResponse) -> Response<Body> {
+    let result = res.result.unwrap_or_default();
+
+    if result.status != Status::Success as i32 {
+        println!("{}", result.result);
+
+        return Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    let http = res
+        .return_value
+        .into_iter()
+        .chain(res.output_data.into_iter().filter_map(|b| b.into_data()))
+        .find_map(|d| match d.data {
+            Some(Data::Http(http)) => Some(http),
+            _ => None,
+        });
+
+    let http = match http {
+        Some(http) => http,
+        None => {
+            return Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .body(Body::empty())
+                .unwrap()
+        }
+    };
+
+    let mut response = Response::builder().status(
+        http.status_code
+            .parse::<u16>()
+            .ok()
+            .and_then(|s| StatusCode::from_u16(s).ok())
+            .unwrap_or(StatusCode::OK),
+    );
+
+    for (name, value) in http.headers.iter() {
+        if let Ok(value) = HeaderValue::from_str(value) {
+            response = response.header(name.as_str(), value);
+        }
+    }
+
+    response
+        .body(match http.body.and_then(|b| b.data) {
+            Some(Data::String(s)) | Some(Data::Json(s)) => Body::from(s),
+            Some(Data::Bytes(b)) | Some(Data::Stream(b)) => Body::from(b),
+            _ => Body::empty(),
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{codegen::bindings::HttpTrigger, fixtures, rpc::StatusResult};
+    use serde_json::json;
+    use std::borrow::Cow;
+
+    static USER_FUNCTION: Function = fixtures::function(
+        "user",
+        &[Binding::HttpTrigger(HttpTrigger {
+            name: Cow::Borrowed("req"),
+            auth_level: None,
+            methods: Cow::Borrowed(&[Cow::Borrowed("get")]),
+            route: Some(Cow::Borrowed("users/{id:int}/{*rest}")),
+        })],
+    );
+
+    #[test]
+    fn it_parses_route_segments() {
+        assert_eq!(
+            Segment::parse("Users"),
+            Segment::Literal("users".to_owned())
+        );
+        assert_eq!(
+            Segment::parse("{id:int}"),
+            Segment::Parameter {
+                name: "id".to_owned(),
+                constraint: Some("int".to_owned()),
+                optional: false,
+                catch_all: false,
+            }
+        );
+        assert_eq!(
+            Segment::parse("{name?}"),
+            Segment::Parameter {
+                name: "name".to_owned(),
+                constraint: None,
+                optional: true,
+                catch_all: false,
+            }
+        );
+        assert_eq!(
+            Segment::parse("{*rest}"),
+            Segment::Parameter {
+                name: "rest".to_owned(),
+                constraint: None,
+                optional: false,
+                catch_all: true,
+            }
+        );
+    }
+
+    #[test]
+    fn it_matches_routes() {
+        let route = Route::new(&USER_FUNCTION).unwrap();
+
+        assert!(route.allows(&Method::GET));
+        assert!(!route.allows(&Method::POST));
+
+        let params = route.matches(&["USERS", "42"]).unwrap();
+        assert_eq!(params.len(), 1);
+        assert_eq!(params["id"], "42");
+
+        let params = route.matches(&["users", "42", "a", "b"]).unwrap();
+        assert_eq!(params["rest"], "a/b");
+
+        assert!(route.matches(&["users", "abc"]).is_none());
+        assert!(route.matches(&["users"]).is_none());
+        assert!(route.matches(&["groups", "42"]).is_none());
+    }
+
+    #[test]
+    fn it_reads_the_route_prefix() {
+        assert_eq!(route_prefix(&json!({})), "api");
+        assert_eq!(
+            route_prefix(&json!({ "extensions": { "http": { "routePrefix": "/v1/" } } })),
+            "v1"
+        );
+        assert_eq!(
+            route_prefix(&json!({ "extensions": { "http": { "routePrefix": "" } } })),
+            ""
+        );
+    }
+
+    #[test]
+    fn it_decodes_route_segments() {
+        assert_eq!(
+            route_segments("API/users/John%20Doe", "api").unwrap(),
+            vec!["users", "John Doe"]
+        );
+        assert_eq!(
+            route_segments("v1/users/42", "v1").unwrap(),
+            vec!["users", "42"]
+        );
+        assert_eq!(route_segments("users/42", "").unwrap(), vec!["users", "42"]);
+        assert!(route_segments("users/42", "api").is_none());
+
+        let route = Route::new(&USER_FUNCTION).unwrap();
+        let segments = route_segments("api/users/42/a%2Fb/c%20d", "api").unwrap();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        let params = route.matches(&segments).unwrap();
+        assert_eq!(params["id"], "42");
+        assert_eq!(params["rest"], "a/b/c d");
+    }
+
+    #[test]
+    fn it_converts_http_output() {
+        let mut headers = HashMap::new();
+        headers.insert("content-type".to_owned(), "text/plain".to_owned());
+
+        let response = to_http_response(InvocationResponse {
+            invocation_id: "1".to_owned(),
+            return_value: Some(TypedData {
+                data: Some(Data::Http(Box::new(RpcHttp {
+                    status_code: "201".to_owned(),
+                    headers,
+                    body: Some(Box::new(TypedData {
+                        data: Some(Data::String("created".to_owned())),
+                    })),
+                    ..Default::default()
+                }))),
+            }),
+            result: Some(StatusResult {
+                status: Status::Success as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["content-type"], "text/plain");
+    }
+}
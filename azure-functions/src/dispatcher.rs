@@ -0,0 +1,104 @@
+use crate::{
+    codegen::Function,
+    logger::LogLevels,
+    rpc::{streaming_message::Content, InvocationRequest, InvocationResponse, StreamingMessage},
+    worker::{Capabilities, Invocations, Sender, Worker},
+};
+use futures::{
+    channel::{
+        mpsc::{unbounded, UnboundedReceiver},
+        oneshot,
+    },
+    stream::StreamExt,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+type Completed = (InvocationResponse, Vec<String>);
+type Pending = Arc<Mutex<HashMap<String, oneshot::Sender<Completed>>>>;
+
+/// Invokes functions outside of the language worker protocol.
+///
+/// Invocations run on the worker's invocation machinery; the response of an invocation is
+/// returned along with the messages logged by the invocation.
+#[derive(Clone)]
+pub struct Dispatcher {
+    invocations: Invocations,
+    pending: Pending,
+    sender: Sender,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Dispatcher {
+    // Must be called from within the runtime that runs the invocations
+    pub fn start() -> Self {
+        let (sender, receiver) = unbounded::<StreamingMessage>();
+        Worker::initialize(sender.clone(), LogLevels::default());
+
+        let pending = Pending::default();
+        tokio::spawn(Dispatcher::dispatch(receiver, pending.clone()));
+
+        Dispatcher {
+            invocations: Invocations::default(),
+            pending,
+            sender,
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    pub fn next_invocation_id(&self) -> String {
+        self.next_id.fetch_add(1, Ordering::SeqCst).to_string()
+    }
+
+    pub async fn invoke(
+        &self,
+        func: &'static Function,
+        req: InvocationRequest,
+    ) -> (InvocationResponse, Vec<String>) {
+        let (sender, completed) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(req.invocation_id.clone(), sender);
+
+        // There is no host to negotiate capabilities with
+        Worker::invoke_function(
+            func,
+            self.invocations.clone(),
+            Capabilities::default(),
+            self.sender.clone(),
+            req,
+        );
+
+        completed.await.expect("expected an invocation response")
+    }
+
+    // Completes pending invocations with their responses and the logs written by them
+    async fn dispatch(mut receiver: UnboundedReceiver<StreamingMessage>, pending: Pending) {
+        let mut logs: HashMap<String, Vec<String>> = HashMap::new();
+
+        while let Some(message) = receiver.next().await {
+            match message.content {
+                Some(Content::RpcLog(log)) => {
+                    if log.invocation_id.is_empty() {
+                        println!("{}", log.message);
+                    } else {
+                        logs.entry(log.invocation_id).or_default().push(log.message);
+                    }
+                }
+                Some(Content::InvocationResponse(res)) => {
+                    let invocation_logs = logs.remove(&res.invocation_id).unwrap_or_default();
+                    if let Some(sender) = pending.lock().unwrap().remove(&res.invocation_id) {
+                        sender.send((res, invocation_logs)).unwrap_or(());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
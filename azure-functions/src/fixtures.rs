@@ -0,0 +1,40 @@
+// Fixtures shared by the tests of the crate
+use crate::codegen::{bindings::Binding, Function};
+use std::borrow::Cow;
+
+// Creates an enabled function with the given bindings and defaults for everything else
+pub(crate) const fn function(name: &'static str, bindings: &'static [Binding]) -> Function {
+    Function {
+        name: Cow::Borrowed(name),
+        disabled: false,
+        bindings: Cow::Borrowed(bindings),
+        invoker: None,
+        middleware: Cow::Borrowed(&[]),
+        max_concurrency: None,
+        timeout: None,
+        manifest_dir: None,
+        file: None,
+    }
+}
+
+// Creates an enabled function without bindings that is invoked with the given invoker function
+#[cfg(feature = "testing")]
+pub(crate) const fn invoked_function(
+    name: &'static str,
+    invoker_fn: crate::codegen::InvokerFn,
+) -> Function {
+    Function {
+        name: Cow::Borrowed(name),
+        disabled: false,
+        bindings: Cow::Borrowed(&[]),
+        invoker: Some(crate::codegen::Invoker {
+            name: Cow::Borrowed(name),
+            invoker_fn,
+        }),
+        middleware: Cow::Borrowed(&[]),
+        max_concurrency: None,
+        timeout: None,
+        manifest_dir: None,
+        file: None,
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     http::Body,
     rpc::{typed_data::Data, TypedData},
 };
-use serde_json::from_str;
+use serde_json::{from_str, json};
 use std::borrow::Cow;
 
 /// Represents a value passed to or from a generic binding.
@@ -48,6 +48,13 @@ impl From<TypedData> for Value {
             Some(Data::Http(_)) => panic!("generic bindings cannot contain HTTP data"),
             Some(Data::Int(i)) => Value::Integer(i),
             Some(Data::Double(d)) => Value::Double(d),
+            Some(Data::CollectionBytes(c)) => Value::Json(json!(c.bytes)),
+            Some(Data::CollectionString(c)) => Value::Json(json!(c.string)),
+            Some(Data::CollectionDouble(c)) => Value::Json(json!(c.double)),
+            Some(Data::CollectionSint64(c)) => Value::Json(json!(c.sint64)),
+            Some(Data::ModelBindingData(_)) | Some(Data::CollectionModelBindingData(_)) => {
+                panic!("generic bindings cannot contain model binding data")
+            }
         }
     }
 }
@@ -79,8 +86,7 @@ impl Into<TypedData> for Value {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rpc::typed_data::Data;
-    use serde_json::json;
+    use crate::rpc::{typed_data::Data, CollectionString};
 
     #[test]
     fn it_converts_from_no_typed_data() {
@@ -149,6 +155,18 @@ mod tests {
         assert_eq!(value, Value::Double(12345.6));
     }
 
+    #[test]
+    fn it_converts_from_collection_typed_data() {
+        let value: Value = TypedData {
+            data: Some(Data::CollectionString(CollectionString {
+                string: vec!["foo".to_owned(), "bar".to_owned()],
+            })),
+        }
+        .into();
+
+        assert_eq!(value, Value::Json(json!(["foo", "bar"])));
+    }
+
     #[test]
     fn it_converts_to_no_typed_data() {
         let data: TypedData = Value::None.into();
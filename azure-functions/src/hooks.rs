@@ -0,0 +1,125 @@
+use futures::future::BoxFuture;
+use log::error;
+use std::{error::Error, future::Future, sync::Arc};
+
+pub(crate) type HookError = Box<dyn Error + Send + Sync>;
+type Hook = Arc<dyn Fn() -> BoxFuture<'static, Result<(), HookError>> + Send + Sync>;
+
+// Represents the lifecycle hooks registered with the worker
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    start: Vec<Hook>,
+    shutdown: Vec<Hook>,
+}
+
+impl Hooks {
+    pub fn add_start<F, Fut, E>(&mut self, hook: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<HookError>,
+    {
+        self.start.push(Hooks::wrap(hook));
+    }
+
+    pub fn add_shutdown<F, Fut, E>(&mut self, hook: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<HookError>,
+    {
+        self.shutdown.push(Hooks::wrap(hook));
+    }
+
+    // Runs the startup hooks in registration order, stopping at the first failure
+    pub async fn start(&self) -> Result<(), HookError> {
+        for hook in self.start.iter() {
+            hook().await?;
+        }
+        Ok(())
+    }
+
+    // Runs the shutdown hooks in reverse registration order; failures are logged
+    pub async fn shutdown(&self) {
+        for hook in self.shutdown.iter().rev() {
+            if let Err(e) = hook().await {
+                error!("Rust worker shutdown hook failed: {}", e);
+            }
+        }
+    }
+
+    fn wrap<F, Fut, E>(hook: F) -> Hook
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<HookError>,
+    {
+        Arc::new(move || {
+            let future = hook();
+            Box::pin(async move { future.await.map_err(Into::into) })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn it_runs_hooks_in_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = Hooks::default();
+
+        for i in 0..2 {
+            let start_calls = calls.clone();
+            hooks.add_start(move || {
+                let calls = start_calls.clone();
+                async move {
+                    calls.lock().unwrap().push(format!("start{}", i));
+                    Ok::<_, HookError>(())
+                }
+            });
+
+            let shutdown_calls = calls.clone();
+            hooks.add_shutdown(move || {
+                let calls = shutdown_calls.clone();
+                async move {
+                    calls.lock().unwrap().push(format!("shutdown{}", i));
+                    Ok::<_, HookError>(())
+                }
+            });
+        }
+
+        hooks.start().await.unwrap();
+        hooks.shutdown().await;
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["start0", "start1", "shutdown1", "shutdown0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_stops_at_the_first_failed_start_hook() {
+        let called = Arc::new(Mutex::new(false));
+        let mut hooks = Hooks::default();
+
+        hooks.add_start(|| async { Err("failed to connect") });
+
+        let second = called.clone();
+        hooks.add_start(move || {
+            let called = second.clone();
+            async move {
+                *called.lock().unwrap() = true;
+                Ok::<_, HookError>(())
+            }
+        });
+
+        assert_eq!(
+            hooks.start().await.unwrap_err().to_string(),
+            "failed to connect"
+        );
+        assert!(!*called.lock().unwrap());
+    }
+}
@@ -91,6 +91,10 @@
 #![deny(missing_docs)]
 #![cfg_attr(test, recursion_limit = "128")]
 
+// Allows the code generated by `#[func]` to refer to this crate in its own tests
+#[cfg(all(test, feature = "testing"))]
+extern crate self as azure_functions;
+
 #[doc(no_inline)]
 pub use azure_functions_codegen::export;
 #[doc(no_inline)]
@@ -101,27 +105,40 @@ pub use azure_functions_shared::codegen;
 
 mod backtrace;
 mod commands;
+mod custom_handler;
+mod dev_server;
+mod dispatcher;
+#[cfg(test)]
+mod fixtures;
+mod hooks;
 mod logger;
 mod registry;
+mod shared_memory;
 mod util;
 mod worker;
+mod worker_builder;
 
 pub mod bindings;
 pub mod blob;
+pub mod concurrency;
 pub mod context;
 pub mod durable;
 pub mod event_hub;
 pub mod generic;
 pub mod http;
+pub mod metrics;
+pub mod middleware;
 pub mod send_grid;
 pub mod signalr;
+pub mod state;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod timer;
+pub mod trace;
 #[doc(no_inline)]
 pub use azure_functions_shared::rpc;
 
-use crate::commands::{Init, Run, SyncExtensions};
-use crate::registry::Registry;
-use clap::{App, AppSettings};
+pub use crate::worker_builder::WorkerBuilder;
 
 #[doc(hidden)]
 pub trait IntoVec<T> {
@@ -133,6 +150,26 @@ pub trait FromVec<T> {
     fn from_vec(vec: Vec<T>) -> Self;
 }
 
+#[doc(hidden)]
+pub fn failure<E>(error: E) -> rpc::StatusResult
+where
+    E: Into<Box<dyn std::error::Error>>,
+{
+    let error = error.into();
+
+    rpc::StatusResult {
+        status: rpc::status_result::Status::Failure as i32,
+        result: error.to_string(),
+        exception: Some(util::to_exception(
+            &*error,
+            context::CURRENT
+                .try_with(|c| c.function_name)
+                .unwrap_or(context::UNKNOWN_FUNCTION),
+        )),
+        ..Default::default()
+    }
+}
+
 /// The main entry point for the Azure Functions for Rust worker.
 ///
 /// This entry point does not use any additional Azure Functions binding extensions.
@@ -174,24 +211,7 @@ pub fn worker_main_with_extensions(
     functions: &[&'static codegen::Function],
     extensions: &[(&str, &str)],
 ) {
-    let registry = Registry::new(functions);
-
-    let app = App::new("Azure Functions for Rust worker")
-        .version(env!("CARGO_PKG_VERSION"))
-        .about("Implements the Azure Functions for Rust worker.")
-        .setting(AppSettings::SubcommandRequiredElseHelp)
-        .setting(AppSettings::VersionlessSubcommands)
-        .subcommand(Init::create_subcommand())
-        .subcommand(SyncExtensions::create_subcommand())
-        .subcommand(Run::create_subcommand());
-
-    if let Err(e) = match app.get_matches_from(args).subcommand() {
-        ("init", Some(args)) => Init::from(args).execute(registry, extensions),
-        ("sync-extensions", Some(args)) => SyncExtensions::from(args).execute(registry, extensions),
-        ("run", Some(args)) => Run::from(args).execute(registry),
-        _ => panic!("expected a subcommand."),
-    } {
-        eprintln!("error: {}", e);
-        std::process::exit(1);
-    }
+    WorkerBuilder::new(functions)
+        .extensions(extensions)
+        .run(args)
 }
@@ -1,23 +1,285 @@
 use crate::{
-    rpc::{rpc_log, streaming_message::Content, RpcLog, StreamingMessage},
+    rpc::{rpc_log, streaming_message::Content, RpcException, RpcLog, StreamingMessage},
+    util::to_exception,
     worker::Sender,
 };
-use log::{Level, Log, Metadata, Record};
+use lazy_static::lazy_static;
+use log::{
+    kv::{self, Key, Value, Visitor},
+    Level, LevelFilter, Log, Metadata, Record,
+};
+use serde_json::{Map, Number};
+use std::{collections::HashMap, sync::RwLock};
+
+// The prefix for environment variables that override host.json logging levels.
+const LOG_LEVEL_ENV_PREFIX: &str = "azurefunctionsjobhost__logging__loglevel__";
+const DEFAULT_CATEGORY: &str = "default";
+const FUNCTION_CATEGORY: &str = "function";
+const WORKER_CATEGORY: &str = "worker";
+// The key of the log property used as the event identifier.
+pub(crate) const EVENT_ID_KEY: &str = "event_id";
+
+lazy_static! {
+    static ref LEVELS: RwLock<LogLevels> = RwLock::new(LogLevels::default());
+    static ref SENDER: RwLock<Option<Sender>> = RwLock::new(None);
+}
+
+#[cfg(any(test, feature = "testing"))]
+lazy_static! {
+    // The sender is global, so tests that set it must hold this lock while it is in use
+    pub(crate) static ref SENDER_LOCK: futures::lock::Mutex<()> = futures::lock::Mutex::new(());
+}
+
+/// Represents the log levels for each log category.
+///
+/// Categories are matched case-insensitively, from the most specific to the least specific:
+/// the record's target and its parent modules, the `Function.<name>` and `Function` categories
+/// when logging from an invocation (otherwise the `Worker` category), and finally the `Default`
+/// category.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLevels {
+    default: LevelFilter,
+    categories: HashMap<String, LevelFilter>,
+}
+
+impl Default for LogLevels {
+    fn default() -> Self {
+        LogLevels {
+            default: LevelFilter::Info,
+            categories: HashMap::new(),
+        }
+    }
+}
+
+impl LogLevels {
+    pub fn from_host(categories: &HashMap<String, i32>) -> Self {
+        let mut levels = LogLevels::default();
+
+        for (category, level) in categories {
+            if let Some(level) = rpc_log::Level::from_i32(*level) {
+                levels.set(category, convert_level(level));
+            }
+        }
+
+        levels
+    }
+
+    pub fn set(&mut self, category: &str, level: LevelFilter) {
+        let category = category.to_lowercase();
+        if category == DEFAULT_CATEGORY {
+            self.default = level;
+        } else {
+            self.categories.insert(category, level);
+        }
+    }
+
+    pub fn apply_environment<I>(&mut self, vars: I)
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        for (name, value) in vars {
+            let name = name.to_lowercase();
+            if !name.starts_with(LOG_LEVEL_ENV_PREFIX) {
+                continue;
+            }
+
+            if let Some(level) = parse_level(&value) {
+                self.set(&name[LOG_LEVEL_ENV_PREFIX.len()..], level);
+            }
+        }
+    }
+
+    pub fn level(&self, target: &str, function_name: Option<&str>) -> LevelFilter {
+        let target = target.to_lowercase();
+        let mut category = target.as_str();
+
+        loop {
+            if let Some(level) = self.categories.get(category) {
+                return *level;
+            }
+
+            match category.rfind("::") {
+                Some(i) => category = &category[..i],
+                None => break,
+            }
+        }
+
+        let level = match function_name {
+            Some(name) => self
+                .categories
+                .get(&format!("{}.{}", FUNCTION_CATEGORY, name.to_lowercase()))
+                .or_else(|| self.categories.get(FUNCTION_CATEGORY)),
+            None => self.categories.get(WORKER_CATEGORY),
+        };
+
+        level.cloned().unwrap_or(self.default)
+    }
+
+    pub fn max_level(&self) -> LevelFilter {
+        self.categories
+            .values()
+            .cloned()
+            .fold(self.default, std::cmp::max)
+    }
+}
+
+fn convert_level(level: rpc_log::Level) -> LevelFilter {
+    match level {
+        rpc_log::Level::Trace => LevelFilter::Trace,
+        rpc_log::Level::Debug => LevelFilter::Debug,
+        rpc_log::Level::Information => LevelFilter::Info,
+        rpc_log::Level::Warning => LevelFilter::Warn,
+        rpc_log::Level::Error | rpc_log::Level::Critical => LevelFilter::Error,
+        rpc_log::Level::None => LevelFilter::Off,
+    }
+}
+
+fn parse_level(level: &str) -> Option<LevelFilter> {
+    match level.to_lowercase().as_str() {
+        "trace" => Some(LevelFilter::Trace),
+        "debug" => Some(LevelFilter::Debug),
+        "information" => Some(LevelFilter::Info),
+        "warning" => Some(LevelFilter::Warn),
+        "error" | "critical" => Some(LevelFilter::Error),
+        "none" => Some(LevelFilter::Off),
+        _ => None,
+    }
+}
+
+/// Replaces the log levels used by the logger.
+pub fn set_levels(levels: LogLevels) {
+    log::set_max_level(levels.max_level());
+    *LEVELS.write().unwrap() = levels;
+}
+
+/// Updates the log levels used by the logger.
+pub fn update_levels<F>(callback: F)
+where
+    F: FnOnce(&mut LogLevels),
+{
+    let mut levels = LEVELS.write().unwrap();
+    callback(&mut levels);
+    log::set_max_level(levels.max_level());
+}
 
-pub struct Logger {
-    level: Level,
-    sender: Sender,
+// Collects the key-value pairs of a log record.
+#[derive(Default)]
+struct Properties {
+    values: Map<String, serde_json::Value>,
+    event_id: Option<String>,
+    exception: Option<RpcException>,
 }
 
-impl Logger {
-    pub fn new(level: Level, sender: Sender) -> Logger {
-        Logger { level, sender }
+impl Properties {
+    fn from_record(record: &Record) -> Self {
+        let mut properties = Properties::default();
+        record.key_values().visit(&mut properties).unwrap_or(());
+
+        if let Some(exception) = properties.exception.as_mut() {
+            exception.source = record.target().to_owned();
+        }
+
+        properties
+    }
+}
+
+impl<'kvs> Visitor<'kvs> for Properties {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        if let Some(error) = value.to_borrowed_error() {
+            if self.exception.is_none() {
+                self.exception = Some(to_exception(error, ""));
+            }
+        }
+
+        if key.as_str() == EVENT_ID_KEY {
+            self.event_id = Some(value.to_string());
+            return Ok(());
+        }
+
+        let value = if let Some(b) = value.to_bool() {
+            serde_json::Value::Bool(b)
+        } else if let Some(n) = value.to_u64() {
+            serde_json::Value::Number(n.into())
+        } else if let Some(n) = value.to_i64() {
+            serde_json::Value::Number(n.into())
+        } else if let Some(n) = value.to_f64().and_then(Number::from_f64) {
+            serde_json::Value::Number(n)
+        } else if let Some(s) = value.to_borrowed_str() {
+            serde_json::Value::String(s.to_owned())
+        } else {
+            serde_json::Value::String(value.to_string())
+        };
+
+        self.values.insert(key.as_str().to_owned(), value);
+        Ok(())
     }
 }
 
+/// Sets the sender used to send log messages to the host.
+pub fn set_sender(sender: Sender) {
+    *SENDER.write().unwrap() = Some(sender);
+}
+
+/// Sends a log message to the host.
+///
+/// The message is written to stderr if it cannot be sent to the host.
+pub fn send(event: RpcLog) {
+    let event = match SENDER.read().unwrap().as_ref() {
+        Some(sender) => match sender.unbounded_send(StreamingMessage {
+            content: Some(Content::RpcLog(event)),
+            ..Default::default()
+        }) {
+            Ok(()) => return,
+            Err(e) => match e.into_inner().content {
+                Some(Content::RpcLog(event)) => event,
+                _ => return,
+            },
+        },
+        None => event,
+    };
+
+    write_stderr(&event);
+}
+
+// Writes a log message that could not be sent to the host to stderr
+pub(crate) fn write_stderr(event: &RpcLog) {
+    eprintln!("{}", event.message);
+}
+
+/// Determines if messages of the given level and target are sent to the host.
+pub fn is_enabled(level: Level, target: &str) -> bool {
+    let function_name = crate::context::CURRENT.try_with(|c| c.function_name).ok();
+    let filter = LEVELS.read().unwrap().level(target, function_name);
+
+    level <= filter
+}
+
+/// Represents the logger that sends log records to the Azure Functions Host.
+///
+/// Key-value pairs of a log record are serialized as the properties of the log message.
+///
+/// The `event_id` key sets the identifier of the log event; log messages without it have no
+/// event identifier.
+///
+/// An error value is also sent as the exception of the log message, with the error's chain
+/// of sources in place of a stack trace.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use log::{error, info};
+///
+/// info!(event_id = "order-created", order_id = order.id, total = order.total; "Order created");
+///
+/// if let Err(e) = process(&order) {
+///     error!(error = log::kv::Value::from_dyn_error(&e); "Failed to process order");
+/// }
+/// ```
+pub struct Logger;
+
 impl Log for Logger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.level
+        is_enabled(metadata.level(), metadata.target())
     }
 
     fn log(&self, record: &Record) {
@@ -25,6 +287,8 @@ impl Log for Logger {
             return;
         }
 
+        let properties = Properties::from_record(record);
+
         let mut event = RpcLog {
             level: match record.level() {
                 Level::Trace => rpc_log::Level::Trace,
@@ -34,18 +298,125 @@ impl Log for Logger {
                 Level::Error => rpc_log::Level::Error,
             } as i32,
             message: record.args().to_string(),
+            category: record.target().to_owned(),
+            event_id: properties.event_id.unwrap_or_default(),
+            exception: properties.exception,
+            properties: if properties.values.is_empty() {
+                String::new()
+            } else {
+                serde_json::to_string(&properties.values).unwrap()
+            },
             ..Default::default()
         };
 
-        event.invocation_id = crate::context::CURRENT.with(|c| c.borrow().invocation_id.clone());
+        event.invocation_id = crate::context::CURRENT
+            .try_with(|c| c.invocation_id.clone())
+            .unwrap_or_default();
 
-        self.sender
-            .unbounded_send(StreamingMessage {
-                content: Some(Content::RpcLog(event)),
-                ..Default::default()
-            })
-            .unwrap_or(());
+        send(event);
     }
 
     fn flush(&self) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_defaults_to_info() {
+        let levels = LogLevels::default();
+
+        assert_eq!(levels.level("example", None), LevelFilter::Info);
+        assert_eq!(levels.max_level(), LevelFilter::Info);
+    }
+
+    #[test]
+    fn it_uses_host_categories() {
+        let mut categories = HashMap::new();
+        categories.insert("Default".to_string(), rpc_log::Level::Warning as i32);
+        categories.insert("Worker".to_string(), rpc_log::Level::Error as i32);
+        categories.insert("Function.Greet".to_string(), rpc_log::Level::Trace as i32);
+        categories.insert("Function.Quiet".to_string(), rpc_log::Level::None as i32);
+
+        let levels = LogLevels::from_host(&categories);
+
+        assert_eq!(levels.level("example", None), LevelFilter::Error);
+        assert_eq!(levels.level("example", Some("greet")), LevelFilter::Trace);
+        assert_eq!(levels.level("example", Some("quiet")), LevelFilter::Off);
+        assert_eq!(levels.level("example", Some("other")), LevelFilter::Warn);
+        assert_eq!(levels.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn it_matches_parent_targets() {
+        let mut levels = LogLevels::default();
+        levels.set("hyper", LevelFilter::Error);
+        levels.set("example::functions", LevelFilter::Debug);
+
+        assert_eq!(
+            levels.level("hyper::client::pool", None),
+            LevelFilter::Error
+        );
+        assert_eq!(
+            levels.level("example::functions::greet", Some("greet")),
+            LevelFilter::Debug
+        );
+        assert_eq!(levels.level("example", None), LevelFilter::Info);
+    }
+
+    #[test]
+    fn it_collects_properties() {
+        let error = std::io::Error::other("oh no");
+        let pairs: &[(&str, Value)] = &[
+            ("event_id", Value::from("created")),
+            ("count", Value::from(42u32)),
+            ("delta", Value::from(-1i32)),
+            ("ratio", Value::from(0.5f64)),
+            ("enabled", Value::from(true)),
+            ("name", Value::from("example")),
+            ("error", Value::from_dyn_error(&error)),
+        ];
+
+        let properties = Properties::from_record(
+            &Record::builder()
+                .target("example")
+                .key_values(&pairs)
+                .build(),
+        );
+
+        assert_eq!(properties.event_id.as_deref(), Some("created"));
+        assert_eq!(
+            serde_json::to_string(&properties.values).unwrap(),
+            r#"{"count":42,"delta":-1,"enabled":true,"error":"oh no","name":"example","ratio":0.5}"#
+        );
+
+        let exception = properties.exception.unwrap();
+        assert_eq!(exception.message, "oh no");
+        assert_eq!(exception.source, "example");
+    }
+
+    #[test]
+    fn it_applies_environment_overrides() {
+        let mut levels = LogLevels::default();
+        levels.apply_environment(vec![
+            (
+                "AzureFunctionsJobHost__logging__logLevel__Default".to_string(),
+                "Debug".to_string(),
+            ),
+            (
+                "AzureFunctionsJobHost__logging__logLevel__Function".to_string(),
+                "Error".to_string(),
+            ),
+            (
+                "AzureFunctionsJobHost__logging__logLevel__example".to_string(),
+                "invalid".to_string(),
+            ),
+            ("UNRELATED".to_string(), "Trace".to_string()),
+        ]);
+
+        assert_eq!(levels.level("example", None), LevelFilter::Debug);
+        assert_eq!(levels.level("example", Some("greet")), LevelFilter::Error);
+        assert_eq!(levels.max_level(), LevelFilter::Debug);
+    }
+}
@@ -0,0 +1,421 @@
+//! Module for worker metrics.
+use crate::{
+    concurrency, logger,
+    rpc::{rpc_log, status_result::Status, InvocationResponse, RpcLog},
+};
+use lazy_static::lazy_static;
+use log::Level;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+// The category of the log messages used to report metrics to the host
+const METRICS_CATEGORY: &str = "azure_functions::metrics";
+// The upper bounds, in seconds, of the latency histogram buckets
+const BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+lazy_static! {
+    static ref METRICS: Mutex<BTreeMap<String, FunctionMetrics>> = Mutex::new(BTreeMap::new());
+}
+
+/// Represents a histogram of invocation latencies.
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    counts: Vec<u64>,
+    sum: Duration,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            counts: vec![0; BUCKETS.len() + 1],
+            sum: Duration::default(),
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        let bucket = BUCKETS
+            .iter()
+            .position(|b| secs <= *b)
+            .unwrap_or(BUCKETS.len());
+
+        self.counts[bucket] += 1;
+        self.sum += duration;
+    }
+
+    /// Gets the number of observed latencies.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Gets the sum of the observed latencies.
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// Gets the cumulative count of observed latencies less than or equal to each bucket's
+    /// upper bound, in seconds.
+    ///
+    /// The last bucket has an upper bound of infinity.
+    pub fn buckets(&self) -> impl Iterator<Item = (f64, u64)> + '_ {
+        BUCKETS
+            .iter()
+            .copied()
+            .chain(std::iter::once(f64::INFINITY))
+            .zip(self.counts.iter().scan(0, |total, count| {
+                *total += count;
+                Some(*total)
+            }))
+    }
+
+    /// Gets the upper bound, in seconds, of the bucket containing the given quantile
+    /// (between 0 and 1) of observed latencies.
+    ///
+    /// Returns None if no latencies have been observed.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+
+        let rank = ((count as f64) * q).ceil().max(1.0) as u64;
+        self.buckets()
+            .find(|(_, total)| *total >= rank)
+            .map(|(bound, _)| bound)
+    }
+}
+
+/// Represents the metrics of an Azure Function maintained by the worker.
+#[derive(Clone, Debug, Default)]
+pub struct FunctionMetrics {
+    /// The number of invocations currently in the worker, including queued invocations.
+    pub in_flight: u64,
+    /// The number of invocations that succeeded.
+    pub succeeded: u64,
+    /// The number of invocations that failed, including invocations that panicked or timed out.
+    pub failed: u64,
+    /// The number of invocations cancelled by the host.
+    pub cancelled: u64,
+    /// The number of invocations that panicked.
+    pub panicked: u64,
+    /// The number of invocations that timed out.
+    pub timed_out: u64,
+    /// The time invocations spent queued because of concurrency limits.
+    pub queue_time: Histogram,
+    /// The total time invocations spent in the worker, including time spent queued.
+    pub duration: Histogram,
+}
+
+fn update<F: FnOnce(&mut FunctionMetrics)>(function: &str, f: F) {
+    let mut metrics = METRICS.lock().unwrap();
+    match metrics.get_mut(function) {
+        Some(m) => f(m),
+        None => f(metrics.entry(function.to_owned()).or_default()),
+    }
+}
+
+pub(crate) fn started(function: &str) {
+    update(function, |m| m.in_flight += 1);
+}
+
+pub(crate) fn dequeued(function: &str, queue_time: Duration) {
+    update(function, |m| m.queue_time.observe(queue_time));
+}
+
+pub(crate) fn timed_out(function: &str) {
+    update(function, |m| m.timed_out += 1);
+}
+
+pub(crate) fn completed(
+    function: &str,
+    res: &InvocationResponse,
+    panicked: bool,
+    duration: Duration,
+) {
+    let status = res.result.as_ref().map(|r| r.status);
+
+    update(function, |m| {
+        m.in_flight = m.in_flight.saturating_sub(1);
+
+        if status == Some(Status::Success as i32) {
+            m.succeeded += 1;
+        } else if status == Some(Status::Cancelled as i32) {
+            m.cancelled += 1;
+        } else {
+            m.failed += 1;
+        }
+
+        if panicked {
+            m.panicked += 1;
+        }
+
+        m.duration.observe(duration);
+    });
+}
+
+/// Gets the metrics of every Azure Function that has been invoked, by function name.
+pub fn snapshot() -> BTreeMap<String, FunctionMetrics> {
+    METRICS.lock().unwrap().clone()
+}
+
+// Reports the metrics of every function to the host as log messages
+pub(crate) fn report() {
+    if !logger::is_enabled(Level::Info, METRICS_CATEGORY) {
+        return;
+    }
+
+    for (function, metrics) in snapshot() {
+        logger::send(RpcLog {
+            level: rpc_log::Level::Information as i32,
+            message: format!("Metrics for Azure Function '{}'.", function),
+            category: METRICS_CATEGORY.to_owned(),
+            event_id: "function_metrics".to_owned(),
+            properties: to_properties(&function, &metrics).to_string(),
+            ..Default::default()
+        });
+    }
+}
+
+fn to_properties(function: &str, metrics: &FunctionMetrics) -> Value {
+    let millis = |secs: Option<f64>| secs.map(|s| s * 1000.0);
+
+    json!({
+        "function": function,
+        "in_flight": metrics.in_flight,
+        "queue_depth": concurrency::function_queue_depth(function),
+        "succeeded": metrics.succeeded,
+        "failed": metrics.failed,
+        "cancelled": metrics.cancelled,
+        "panicked": metrics.panicked,
+        "timed_out": metrics.timed_out,
+        "duration_ms_sum": metrics.duration.sum().as_secs_f64() * 1000.0,
+        "duration_ms_p50": millis(metrics.duration.quantile(0.5)),
+        "duration_ms_p95": millis(metrics.duration.quantile(0.95)),
+        "duration_ms_p99": millis(metrics.duration.quantile(0.99)),
+        "queue_ms_sum": metrics.queue_time.sum().as_secs_f64() * 1000.0,
+        "queue_ms_p95": millis(metrics.queue_time.quantile(0.95)),
+    })
+}
+
+// Renders the metrics in the Prometheus text format
+pub(crate) fn to_prometheus() -> String {
+    let metrics = snapshot();
+    let mut text = String::new();
+
+    writeln!(text, "# TYPE azure_functions_queue_depth gauge").unwrap();
+    writeln!(
+        text,
+        "azure_functions_queue_depth {}",
+        concurrency::queue_depth()
+    )
+    .unwrap();
+
+    write_values(
+        &mut text,
+        "azure_functions_invocations_in_flight",
+        "gauge",
+        &metrics,
+        |_, m| m.in_flight,
+    );
+    write_values(
+        &mut text,
+        "azure_functions_function_queue_depth",
+        "gauge",
+        &metrics,
+        |f, _| concurrency::function_queue_depth(f) as u64,
+    );
+    write_values(
+        &mut text,
+        "azure_functions_invocation_panics_total",
+        "counter",
+        &metrics,
+        |_, m| m.panicked,
+    );
+    write_values(
+        &mut text,
+        "azure_functions_invocation_timeouts_total",
+        "counter",
+        &metrics,
+        |_, m| m.timed_out,
+    );
+
+    writeln!(text, "# TYPE azure_functions_invocations_total counter").unwrap();
+    for (function, m) in metrics.iter() {
+        for (outcome, count) in &[
+            ("succeeded", m.succeeded),
+            ("failed", m.failed),
+            ("cancelled", m.cancelled),
+        ] {
+            writeln!(
+                text,
+                "azure_functions_invocations_total{{function=\"{}\",outcome=\"{}\"}} {}",
+                function, outcome, count
+            )
+            .unwrap();
+        }
+    }
+
+    write_histogram(
+        &mut text,
+        "azure_functions_invocation_duration_seconds",
+        &metrics,
+        |m| &m.duration,
+    );
+    write_histogram(
+        &mut text,
+        "azure_functions_invocation_queue_seconds",
+        &metrics,
+        |m| &m.queue_time,
+    );
+
+    text
+}
+
+fn write_values<F>(
+    text: &mut String,
+    name: &str,
+    kind: &str,
+    metrics: &BTreeMap<String, FunctionMetrics>,
+    value: F,
+) where
+    F: Fn(&str, &FunctionMetrics) -> u64,
+{
+    writeln!(text, "# TYPE {} {}", name, kind).unwrap();
+    for (function, m) in metrics.iter() {
+        writeln!(
+            text,
+            "{}{{function=\"{}\"}} {}",
+            name,
+            function,
+            value(function, m)
+        )
+        .unwrap();
+    }
+}
+
+fn write_histogram<F>(
+    text: &mut String,
+    name: &str,
+    metrics: &BTreeMap<String, FunctionMetrics>,
+    histogram: F,
+) where
+    F: Fn(&FunctionMetrics) -> &Histogram,
+{
+    writeln!(text, "# TYPE {} histogram", name).unwrap();
+    for (function, m) in metrics.iter() {
+        let histogram = histogram(m);
+
+        for (bound, count) in histogram.buckets() {
+            let le = if bound.is_infinite() {
+                "+Inf".to_owned()
+            } else {
+                bound.to_string()
+            };
+
+            writeln!(
+                text,
+                "{}_bucket{{function=\"{}\",le=\"{}\"}} {}",
+                name, function, le, count
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            text,
+            "{}_sum{{function=\"{}\"}} {}",
+            name,
+            function,
+            histogram.sum().as_secs_f64()
+        )
+        .unwrap();
+        writeln!(
+            text,
+            "{}_count{{function=\"{}\"}} {}",
+            name,
+            function,
+            histogram.count()
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::StatusResult;
+
+    fn response(status: Status) -> InvocationResponse {
+        InvocationResponse {
+            result: Some(StatusResult {
+                status: status as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_observes_latencies() {
+        let mut histogram = Histogram::default();
+
+        assert_eq!(histogram.quantile(0.5), None);
+
+        histogram.observe(Duration::from_millis(3));
+        histogram.observe(Duration::from_millis(40));
+        histogram.observe(Duration::from_millis(45));
+        histogram.observe(Duration::from_secs(90));
+
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(histogram.sum(), Duration::from_millis(90_088));
+        assert_eq!(histogram.quantile(0.5), Some(0.05));
+        assert_eq!(histogram.quantile(0.99), Some(f64::INFINITY));
+
+        let buckets: Vec<_> = histogram.buckets().collect();
+        assert_eq!(buckets.len(), BUCKETS.len() + 1);
+        assert_eq!(buckets[0], (0.005, 1));
+        assert_eq!(buckets[3], (0.05, 3));
+        assert_eq!(buckets[BUCKETS.len()], (f64::INFINITY, 4));
+    }
+
+    #[test]
+    fn it_records_invocation_outcomes() {
+        started("metrics_test");
+        started("metrics_test");
+        started("metrics_test");
+        dequeued("metrics_test", Duration::from_millis(1));
+        completed(
+            "metrics_test",
+            &response(Status::Success),
+            false,
+            Duration::from_millis(2),
+        );
+        timed_out("metrics_test");
+        completed(
+            "metrics_test",
+            &response(Status::Failure),
+            false,
+            Duration::from_secs(1),
+        );
+
+        let metrics = snapshot().remove("metrics_test").unwrap();
+        assert_eq!(metrics.in_flight, 1);
+        assert_eq!(metrics.succeeded, 1);
+        assert_eq!(metrics.failed, 1);
+        assert_eq!(metrics.timed_out, 1);
+        assert_eq!(metrics.queue_time.count(), 1);
+        assert_eq!(metrics.duration.count(), 2);
+
+        let text = to_prometheus();
+        assert!(text.contains("azure_functions_invocations_in_flight{function=\"metrics_test\"} 1"));
+        assert!(text.contains(
+            "azure_functions_invocations_total{function=\"metrics_test\",outcome=\"succeeded\"} 1"
+        ));
+        assert!(text.contains(
+            "azure_functions_invocation_duration_seconds_bucket{function=\"metrics_test\",le=\"+Inf\"} 2"
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! Module for invocation middleware.
+use crate::{
+    codegen::Function,
+    rpc::{InvocationRequest, InvocationResponse},
+};
+use futures::future::BoxFuture;
+use lazy_static::lazy_static;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+// Invokes the function itself at the end of the middleware pipeline
+type Endpoint = dyn Fn(&'static Function, InvocationRequest) -> BoxFuture<'static, InvocationResponse>
+    + Send
+    + Sync;
+
+// Represents the middleware registered with the worker
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareMap {
+    pub global: Vec<Arc<dyn Middleware>>,
+    pub named: HashMap<String, Arc<dyn Middleware>>,
+}
+
+lazy_static! {
+    static ref MIDDLEWARE: RwLock<MiddlewareMap> = RwLock::new(MiddlewareMap::default());
+}
+
+// Makes the given middleware available to the worker
+pub(crate) fn register(middleware: MiddlewareMap) {
+    *MIDDLEWARE.write().unwrap() = middleware;
+}
+
+// Checks that the named middleware of every function has been registered
+pub(crate) fn validate<'a>(functions: impl Iterator<Item = &'a Function>) -> Result<(), String> {
+    let registered = MIDDLEWARE.read().unwrap();
+
+    for func in functions {
+        for name in func.middleware.iter() {
+            if !registered.named.contains_key(name.as_ref()) {
+                return Err(format!(
+                    "Azure Function '{}' uses middleware '{}' that has not been registered.",
+                    func.name, name
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Runs the middleware pipeline of the given function
+pub(crate) fn run<E>(
+    func: &'static Function,
+    req: InvocationRequest,
+    endpoint: E,
+) -> BoxFuture<'static, InvocationResponse>
+where
+    E: Fn(&'static Function, InvocationRequest) -> BoxFuture<'static, InvocationResponse>
+        + Send
+        + Sync
+        + 'static,
+{
+    let pipeline: Vec<Arc<dyn Middleware>> = {
+        let registered = MIDDLEWARE.read().unwrap();
+
+        registered
+            .global
+            .iter()
+            .cloned()
+            .chain(
+                func.middleware
+                    .iter()
+                    .filter_map(|name| registered.named.get(name.as_ref()).cloned()),
+            )
+            .collect()
+    };
+
+    Box::pin(async move {
+        Next {
+            pipeline: &pipeline,
+            func,
+            endpoint: &endpoint,
+        }
+        .run(req)
+        .await
+    })
+}
+
+/// Represents middleware that runs around every invocation of an Azure Function.
+///
+/// Middleware can inspect or change the invocation request, including the trigger metadata
+/// and input bindings, before calling `next.run` to continue the invocation. It can also
+/// short-circuit the invocation by returning a response without calling `next.run`, or
+/// post-process the response, including its output bindings and return value.
+///
+/// Middleware is registered with `WorkerBuilder::middleware` to run for every function, or
+/// with `WorkerBuilder::named_middleware` to run for the functions that name it with the
+/// `middleware` argument of the `func` attribute (e.g. `#[func(middleware = "auth|timing")]`).
+///
+/// Middleware runs with the invocation context of the invocation.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::{
+///     middleware::{Middleware, Next},
+///     rpc::{InvocationRequest, InvocationResponse},
+/// };
+/// use futures::future::BoxFuture;
+/// use log::info;
+/// use std::time::Instant;
+///
+/// struct Timing;
+///
+/// impl Middleware for Timing {
+///     fn handle<'a>(
+///         &'a self,
+///         req: InvocationRequest,
+///         next: Next<'a>,
+///     ) -> BoxFuture<'a, InvocationResponse> {
+///         Box::pin(async move {
+///             let start = Instant::now();
+///             let function = next.function_name().to_owned();
+///
+///             let res = next.run(req).await;
+///
+///             info!("Function '{}' took {:?}.", function, start.elapsed());
+///             res
+///         })
+///     }
+/// }
+/// ```
+pub trait Middleware: Send + Sync + 'static {
+    /// Handles an invocation request.
+    ///
+    /// Call `next.run` to continue the invocation with the next middleware or the function.
+    fn handle<'a>(
+        &'a self,
+        req: InvocationRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, InvocationResponse>;
+}
+
+/// Represents the remainder of the middleware pipeline of an invocation.
+pub struct Next<'a> {
+    pipeline: &'a [Arc<dyn Middleware>],
+    func: &'static Function,
+    endpoint: &'a Endpoint,
+}
+
+impl<'a> Next<'a> {
+    /// Gets the name of the Azure Function being invoked.
+    pub fn function_name(&self) -> &str {
+        &self.func.name
+    }
+
+    /// Continues the invocation with the next middleware or, if there is none, the function.
+    pub async fn run(self, req: InvocationRequest) -> InvocationResponse {
+        match self.pipeline.split_first() {
+            Some((middleware, rest)) => {
+                middleware
+                    .handle(
+                        req,
+                        Next {
+                            pipeline: rest,
+                            func: self.func,
+                            endpoint: self.endpoint,
+                        },
+                    )
+                    .await
+            }
+            None => (self.endpoint)(self.func, req).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures,
+        rpc::{status_result::Status, StatusResult},
+    };
+    use std::sync::Mutex;
+
+    static FUNCTION: Function = fixtures::function("function", &[]);
+
+    struct Record(&'static str, Arc<Mutex<Vec<&'static str>>>);
+
+    impl Middleware for Record {
+        fn handle<'a>(
+            &'a self,
+            req: InvocationRequest,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, InvocationResponse> {
+            Box::pin(async move {
+                self.1.lock().unwrap().push(self.0);
+                next.run(req).await
+            })
+        }
+    }
+
+    struct Reject;
+
+    impl Middleware for Reject {
+        fn handle<'a>(
+            &'a self,
+            req: InvocationRequest,
+            _: Next<'a>,
+        ) -> BoxFuture<'a, InvocationResponse> {
+            Box::pin(async move {
+                InvocationResponse {
+                    invocation_id: req.invocation_id,
+                    result: Some(StatusResult {
+                        status: Status::Failure as i32,
+                        result: "rejected".to_owned(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+            })
+        }
+    }
+
+    fn endpoint(calls: Arc<Mutex<Vec<&'static str>>>) -> Box<Endpoint> {
+        Box::new(move |_, req| {
+            calls.lock().unwrap().push("function");
+            Box::pin(futures::future::ready(InvocationResponse {
+                invocation_id: req.invocation_id,
+                ..Default::default()
+            }))
+        })
+    }
+
+    #[tokio::test]
+    async fn it_runs_middleware_in_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let pipeline: Vec<Arc<dyn Middleware>> = vec![
+            Arc::new(Record("first", calls.clone())),
+            Arc::new(Record("second", calls.clone())),
+        ];
+        let endpoint = endpoint(calls.clone());
+
+        let res = Next {
+            pipeline: &pipeline,
+            func: &FUNCTION,
+            endpoint: &*endpoint,
+        }
+        .run(InvocationRequest {
+            invocation_id: "1".to_owned(),
+            ..Default::default()
+        })
+        .await;
+
+        assert_eq!(res.invocation_id, "1");
+        assert_eq!(*calls.lock().unwrap(), vec!["first", "second", "function"]);
+    }
+
+    #[tokio::test]
+    async fn it_short_circuits_the_invocation() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let pipeline: Vec<Arc<dyn Middleware>> =
+            vec![Arc::new(Reject), Arc::new(Record("second", calls.clone()))];
+        let endpoint = endpoint(calls.clone());
+
+        let res = Next {
+            pipeline: &pipeline,
+            func: &FUNCTION,
+            endpoint: &*endpoint,
+        }
+        .run(InvocationRequest {
+            invocation_id: "1".to_owned(),
+            ..Default::default()
+        })
+        .await;
+
+        assert_eq!(res.result.unwrap().result, "rejected");
+        assert!(calls.lock().unwrap().is_empty());
+    }
+}
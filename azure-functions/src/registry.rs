@@ -1,8 +1,21 @@
-use crate::codegen::{bindings, Function};
+use crate::{
+    codegen::{
+        bindings::{self, Binding},
+        Function,
+    },
+    rpc::{
+        binding_info::{DataType, Direction},
+        BindingInfo, RpcFunctionMetadata,
+    },
+};
 use lazy_static::lazy_static;
 use semver::Version;
+use serde_json::{to_string, to_value};
 use std::collections::{hash_map::Iter, HashMap};
 
+// The language of the functions reported to the host; this must match the worker configuration
+const LANGUAGE: &str = "Rust";
+
 // Note: package names are expected to be lowercase.
 const STORAGE_PACKAGE_NAME: &str = "microsoft.azure.webjobs.extensions.storage";
 const STORAGE_PACKAGE_VERSION: &str = "3.0.3";
@@ -146,6 +159,66 @@ impl<'a> Registry<'a> {
         self.functions.iter()
     }
 
+    // Builds the metadata of the functions for the host when the worker indexes the functions
+    //
+    // Functions are identified by name; disabled functions are not reported to the host.
+    pub fn build_metadata(&self, directory: &str, script_file: &str) -> Vec<RpcFunctionMetadata> {
+        let mut metadata: Vec<_> = self
+            .functions
+            .values()
+            .filter(|func| !func.disabled)
+            .map(|func| RpcFunctionMetadata {
+                name: func.name.to_string(),
+                function_id: func.name.to_string(),
+                directory: directory.to_owned(),
+                script_file: script_file.to_owned(),
+                entry_point: func.name.to_string(),
+                language: LANGUAGE.to_owned(),
+                bindings: func
+                    .bindings
+                    .iter()
+                    .filter_map(Self::binding_info)
+                    .collect(),
+                raw_bindings: func
+                    .bindings
+                    .iter()
+                    .map(|b| to_string(b).expect("failed to serialize binding"))
+                    .collect(),
+                ..Default::default()
+            })
+            .collect();
+
+        metadata.sort_by(|a, b| a.name.cmp(&b.name));
+        metadata
+    }
+
+    fn binding_info(binding: &Binding) -> Option<(String, BindingInfo)> {
+        let binding = to_value(binding).ok()?;
+
+        let direction = match binding["direction"].as_str() {
+            Some("out") => Direction::Out,
+            Some("inout") => Direction::Inout,
+            _ => Direction::In,
+        };
+
+        let data_type = match binding["dataType"].as_str() {
+            Some("string") => DataType::String,
+            Some("binary") => DataType::Binary,
+            Some("stream") => DataType::Stream,
+            _ => DataType::Undefined,
+        };
+
+        Some((
+            binding["name"].as_str()?.to_owned(),
+            BindingInfo {
+                r#type: binding["type"].as_str()?.to_owned(),
+                direction: direction as i32,
+                data_type: data_type as i32,
+                ..Default::default()
+            },
+        ))
+    }
+
     pub fn build_extensions_map(&self, extensions: &[(&str, &str)]) -> HashMap<String, String> {
         let mut map = HashMap::new();
 
@@ -183,7 +256,10 @@ impl<'a> Registry<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::codegen::bindings::{Binding, Direction};
+    use crate::{
+        codegen::bindings::{Binding, Direction},
+        rpc,
+    };
     use std::borrow::Cow;
 
     #[test]
@@ -200,6 +276,9 @@ mod tests {
                 disabled: false,
                 bindings: Cow::Borrowed(&[]),
                 invoker: None,
+                middleware: Cow::Borrowed(&[]),
+                max_concurrency: None,
+                timeout: None,
                 manifest_dir: None,
                 file: None,
             },
@@ -208,6 +287,9 @@ mod tests {
                 disabled: false,
                 bindings: Cow::Borrowed(&[]),
                 invoker: None,
+                middleware: Cow::Borrowed(&[]),
+                max_concurrency: None,
+                timeout: None,
                 manifest_dir: None,
                 file: None,
             },
@@ -216,6 +298,9 @@ mod tests {
                 disabled: false,
                 bindings: Cow::Borrowed(&[]),
                 invoker: None,
+                middleware: Cow::Borrowed(&[]),
+                max_concurrency: None,
+                timeout: None,
                 manifest_dir: None,
                 file: None,
             },
@@ -233,6 +318,9 @@ mod tests {
             disabled: false,
             bindings: Cow::Borrowed(&[]),
             invoker: None,
+            middleware: Cow::Borrowed(&[]),
+            max_concurrency: None,
+            timeout: None,
             manifest_dir: None,
             file: None,
         }]);
@@ -253,6 +341,9 @@ mod tests {
             disabled: false,
             bindings: Cow::Borrowed(&[]),
             invoker: None,
+            middleware: Cow::Borrowed(&[]),
+            max_concurrency: None,
+            timeout: None,
             manifest_dir: None,
             file: None,
         }]);
@@ -261,6 +352,83 @@ mod tests {
         assert_eq!(registry.register("id", "not_present"), false);
     }
 
+    #[test]
+    fn it_builds_function_metadata() {
+        let registry = Registry::new(&[
+            &Function {
+                name: Cow::Borrowed("function2"),
+                disabled: false,
+                bindings: Cow::Borrowed(&[
+                    Binding::Queue(bindings::Queue {
+                        name: Cow::Borrowed("output1"),
+                        queue_name: Cow::Borrowed("some_queue"),
+                        connection: None,
+                    }),
+                    Binding::Blob(bindings::Blob {
+                        name: Cow::Borrowed("blob"),
+                        path: Cow::Borrowed("some_path"),
+                        connection: None,
+                        direction: Direction::InOut,
+                    }),
+                ]),
+                invoker: None,
+                middleware: Cow::Borrowed(&[]),
+                max_concurrency: None,
+                timeout: None,
+                manifest_dir: None,
+                file: None,
+            },
+            &Function {
+                name: Cow::Borrowed("function1"),
+                disabled: false,
+                bindings: Cow::Borrowed(&[]),
+                invoker: None,
+                middleware: Cow::Borrowed(&[]),
+                max_concurrency: None,
+                timeout: None,
+                manifest_dir: None,
+                file: None,
+            },
+            &Function {
+                name: Cow::Borrowed("disabled"),
+                disabled: true,
+                bindings: Cow::Borrowed(&[]),
+                invoker: None,
+                middleware: Cow::Borrowed(&[]),
+                max_concurrency: None,
+                timeout: None,
+                manifest_dir: None,
+                file: None,
+            },
+        ]);
+
+        let metadata = registry.build_metadata("/app", "worker");
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata[0].name, "function1");
+
+        let function2 = &metadata[1];
+        assert_eq!(function2.name, "function2");
+        assert_eq!(function2.function_id, "function2");
+        assert_eq!(function2.directory, "/app");
+        assert_eq!(function2.script_file, "worker");
+        assert_eq!(function2.language, "Rust");
+        assert_eq!(
+            function2.raw_bindings,
+            vec![
+                r#"{"type":"queue","direction":"out","name":"output1","queueName":"some_queue"}"#,
+                r#"{"type":"blob","direction":"inout","name":"blob","path":"some_path"}"#,
+            ]
+        );
+
+        let queue = &function2.bindings["output1"];
+        assert_eq!(queue.r#type, "queue");
+        assert_eq!(queue.direction, rpc::binding_info::Direction::Out as i32);
+
+        let blob = &function2.bindings["blob"];
+        assert_eq!(blob.r#type, "blob");
+        assert_eq!(blob.direction, rpc::binding_info::Direction::Inout as i32);
+    }
+
     #[test]
     fn it_builds_an_extensions_map() {
         let registry = Registry::new(&[&Function {
@@ -283,6 +451,9 @@ mod tests {
                 }),
             ]),
             invoker: None,
+            middleware: Cow::Borrowed(&[]),
+            max_concurrency: None,
+            timeout: None,
             manifest_dir: None,
             file: None,
         }]);
@@ -306,6 +477,9 @@ mod tests {
                 connection: None,
             })]),
             invoker: None,
+            middleware: Cow::Borrowed(&[]),
+            max_concurrency: None,
+            timeout: None,
             manifest_dir: None,
             file: None,
         }]);
@@ -325,6 +499,9 @@ mod tests {
                 name: Cow::Borrowed("binding1"),
             })]),
             invoker: None,
+            middleware: Cow::Borrowed(&[]),
+            max_concurrency: None,
+            timeout: None,
             manifest_dir: None,
             file: None,
         }]);
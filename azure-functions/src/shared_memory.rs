@@ -0,0 +1,444 @@
+//! Module for transferring binding data with the host through shared memory.
+//!
+//! Large payloads are exchanged through memory-mapped files rather than the gRPC stream.
+//! Each memory map starts with a header of a one byte initialized flag followed by the
+//! length of the content as a native-endian 64-bit integer.
+use crate::{
+    codegen::{
+        bindings::{Binding, Direction},
+        Function,
+    },
+    rpc::{
+        parameter_binding::RpcData, typed_data::Data, InvocationRequest, InvocationResponse,
+        RpcDataType, RpcSharedMemory, TypedData,
+    },
+};
+use lazy_static::lazy_static;
+use log::warn;
+use memmap2::{Mmap, MmapMut};
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+// The environment variable containing the directories the host creates memory maps in
+const DIRECTORIES_VAR: &str = "FUNCTIONS_UNIX_SHARED_MEMORY_DIRECTORIES";
+const DEFAULT_DIRECTORY: &str = "/dev/shm";
+// The subdirectory of each shared memory directory that contains the memory maps
+const SUBDIRECTORY: &str = "AzureFunctions";
+
+const INITIALIZED_FLAG: u8 = 1;
+const HEADER_LENGTH: usize = 1 + 8;
+
+// Outputs smaller than this are sent through the gRPC stream
+const MIN_TRANSFER_LENGTH: usize = 1024 * 1024;
+// The host cannot read memory maps larger than this
+const MAX_TRANSFER_LENGTH: usize = i32::MAX as usize;
+
+lazy_static! {
+    // The memory maps created by the worker, kept open until the host closes them
+    static ref MAPS: Mutex<HashMap<String, (PathBuf, MmapMut)>> = Mutex::new(HashMap::new());
+}
+
+static NEXT_MAP_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Gets the directories that contain the memory maps
+pub(crate) fn directories() -> Vec<PathBuf> {
+    env::var(DIRECTORIES_VAR)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_DIRECTORY.to_owned())
+        .split(',')
+        .map(|d| PathBuf::from(d.trim()).join(SUBDIRECTORY))
+        .collect()
+}
+
+// Reads the input bindings of the request that were transferred through shared memory
+pub(crate) fn read_inputs(
+    req: &mut InvocationRequest,
+    directories: &[PathBuf],
+) -> Result<(), String> {
+    for binding in req.input_data.iter_mut() {
+        if let Some(RpcData::RpcSharedMemory(memory)) = &binding.rpc_data {
+            let data = read(memory, directories).map_err(|e| {
+                format!(
+                    "failed to read binding '{}' from shared memory: {}",
+                    binding.name, e
+                )
+            })?;
+            binding.rpc_data = Some(RpcData::Data(data));
+        }
+    }
+
+    Ok(())
+}
+
+// Transfers the blob output bindings of the response through shared memory
+//
+// Bindings that are too small or that cannot be written to shared memory are sent through gRPC.
+pub(crate) fn write_outputs(
+    func: &Function,
+    mut res: InvocationResponse,
+    directories: &[PathBuf],
+) -> InvocationResponse {
+    for binding in res.output_data.iter_mut() {
+        let is_blob = func.bindings.iter().any(|b| match b {
+            Binding::Blob(b) => b.name == binding.name && b.direction != Direction::In,
+            _ => false,
+        });
+        if !is_blob {
+            continue;
+        }
+
+        let (content, data_type) = match binding.data().and_then(|d| d.data.as_ref()) {
+            Some(Data::Bytes(b)) | Some(Data::Stream(b)) => (b.as_slice(), RpcDataType::Bytes),
+            Some(Data::String(s)) => (s.as_bytes(), RpcDataType::String),
+            _ => continue,
+        };
+
+        let length = content.len();
+        if !(MIN_TRANSFER_LENGTH..=MAX_TRANSFER_LENGTH).contains(&length) {
+            continue;
+        }
+
+        match create(content, directories) {
+            Ok(name) => {
+                binding.rpc_data = Some(RpcData::RpcSharedMemory(RpcSharedMemory {
+                    name,
+                    offset: 0,
+                    count: length as i64,
+                    r#type: data_type as i32,
+                }))
+            }
+            Err(e) => warn!(
+                "Failed to write binding '{}' of Azure Function '{}' to shared memory; sending it to the host instead: {}",
+                binding.name, func.name, e
+            ),
+        }
+    }
+
+    res
+}
+
+// Closes the given memory maps, returning whether each map was closed
+//
+// Memory maps created by the host are not held open by the worker and are always reported as closed.
+pub(crate) fn close(names: &[String]) -> HashMap<String, bool> {
+    let mut maps = MAPS.lock().unwrap();
+
+    names
+        .iter()
+        .map(|name| {
+            let closed = match maps.remove(name) {
+                Some((path, map)) => {
+                    drop(map);
+                    fs::remove_file(&path).is_ok()
+                }
+                None => true,
+            };
+            (name.clone(), closed)
+        })
+        .collect()
+}
+
+// Closes every memory map created by the worker in the given directories
+//
+// The host only closes the memory maps of responses it has received, so the maps that remain
+// when the session with the host ends would otherwise never be removed.
+pub(crate) fn close_all(directories: &[PathBuf]) {
+    let names: Vec<String> = MAPS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, (path, _))| directories.iter().any(|d| path.starts_with(d)))
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    close(&names);
+}
+
+fn read(memory: &RpcSharedMemory, directories: &[PathBuf]) -> Result<TypedData, String> {
+    let file = directories
+        .iter()
+        .map(|d| d.join(&memory.name))
+        .find_map(|p| File::open(p).ok())
+        .ok_or_else(|| format!("memory map '{}' does not exist", memory.name))?;
+
+    // The host does not modify a memory map until the worker closes it
+    let map = unsafe { Mmap::map(&file) }
+        .map_err(|e| format!("failed to map '{}': {}", memory.name, e))?;
+
+    if map.len() < HEADER_LENGTH || map[0] != INITIALIZED_FLAG {
+        return Err(format!("memory map '{}' is not initialized", memory.name));
+    }
+
+    let mut length = [0; 8];
+    length.copy_from_slice(&map[1..HEADER_LENGTH]);
+    let length = i64::from_ne_bytes(length);
+
+    let end = match memory.offset.checked_add(memory.count) {
+        Some(end) if memory.offset >= 0 && memory.count >= 0 && end <= length => end,
+        _ => {
+            return Err(format!(
+                "{} bytes at offset {} are outside of the {} bytes of memory map '{}'",
+                memory.count, memory.offset, length, memory.name
+            ))
+        }
+    };
+
+    let content = map[HEADER_LENGTH..]
+        .get(memory.offset as usize..end as usize)
+        .ok_or_else(|| format!("memory map '{}' is truncated", memory.name))?
+        .to_vec();
+
+    let data = match RpcDataType::from_i32(memory.r#type) {
+        Some(RpcDataType::Bytes) => Data::Bytes(content),
+        Some(RpcDataType::String) => Data::String(
+            String::from_utf8(content)
+                .map_err(|e| format!("memory map '{}' is not valid UTF-8: {}", memory.name, e))?,
+        ),
+        _ => {
+            return Err(format!(
+                "unsupported data type {} for memory map '{}'",
+                memory.r#type, memory.name
+            ))
+        }
+    };
+
+    Ok(TypedData { data: Some(data) })
+}
+
+fn create(content: &[u8], directories: &[PathBuf]) -> Result<String, String> {
+    let name = format!(
+        "rust-{}-{}",
+        process::id(),
+        NEXT_MAP_ID.fetch_add(1, Ordering::SeqCst)
+    );
+
+    let mut errors = Vec::new();
+    for directory in directories {
+        let path = directory.join(&name);
+        match create_in(&path, content) {
+            Ok(map) => {
+                MAPS.lock().unwrap().insert(name.clone(), (path, map));
+                return Ok(name);
+            }
+            Err(e) => {
+                fs::remove_file(&path).unwrap_or(());
+                errors.push(format!("{}: {}", directory.display(), e));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        return Err("no shared memory directories are configured".to_owned());
+    }
+
+    Err(errors.join("; "))
+}
+
+fn create_in(path: &Path, content: &[u8]) -> std::io::Result<MmapMut> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    file.set_len((HEADER_LENGTH + content.len()) as u64)?;
+
+    // The file was created above and is not shared until its name is sent to the host
+    let mut map = unsafe { MmapMut::map_mut(&file) }?;
+    map[1..HEADER_LENGTH].copy_from_slice(&(content.len() as i64).to_ne_bytes());
+    map[HEADER_LENGTH..].copy_from_slice(content);
+    map[0] = INITIALIZED_FLAG;
+    map.flush()?;
+
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{codegen::bindings, fixtures, rpc::ParameterBinding};
+    use std::borrow::Cow;
+
+    static FUNCTION: Function = fixtures::function(
+        "upload",
+        &[
+            Binding::Blob(bindings::Blob {
+                name: Cow::Borrowed("blob"),
+                path: Cow::Borrowed("some_path"),
+                connection: None,
+                direction: Direction::Out,
+            }),
+            Binding::Blob(bindings::Blob {
+                name: Cow::Borrowed("small"),
+                path: Cow::Borrowed("some_path"),
+                connection: None,
+                direction: Direction::Out,
+            }),
+        ],
+    );
+
+    fn bytes(len: usize) -> TypedData {
+        TypedData {
+            data: Some(Data::Bytes((0..len).map(|i| i as u8).collect())),
+        }
+    }
+
+    #[test]
+    fn it_transfers_blobs_through_shared_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let directories = vec![dir.path().join(SUBDIRECTORY)];
+
+        let res = write_outputs(
+            &FUNCTION,
+            InvocationResponse {
+                output_data: vec![
+                    ParameterBinding::new("blob", bytes(2 * MIN_TRANSFER_LENGTH)),
+                    ParameterBinding::new("small", bytes(16)),
+                    ParameterBinding::new("queue", bytes(2 * MIN_TRANSFER_LENGTH)),
+                ],
+                ..Default::default()
+            },
+            &directories,
+        );
+
+        let memory = match &res.output_data[0].rpc_data {
+            Some(RpcData::RpcSharedMemory(memory)) => memory.clone(),
+            _ => panic!("expected the blob to be in shared memory"),
+        };
+        assert_eq!(memory.count, 2 * MIN_TRANSFER_LENGTH as i64);
+        assert_eq!(memory.r#type, RpcDataType::Bytes as i32);
+        assert_eq!(res.output_data[1].data(), Some(&bytes(16)));
+        assert_eq!(
+            res.output_data[2].data(),
+            Some(&bytes(2 * MIN_TRANSFER_LENGTH))
+        );
+
+        let mut req = InvocationRequest {
+            input_data: vec![ParameterBinding {
+                name: "blob".to_owned(),
+                rpc_data: Some(RpcData::RpcSharedMemory(memory.clone())),
+            }],
+            ..Default::default()
+        };
+        read_inputs(&mut req, &directories).unwrap();
+        assert_eq!(
+            req.input_data[0].data(),
+            Some(&bytes(2 * MIN_TRANSFER_LENGTH))
+        );
+
+        let path = directories[0].join(&memory.name);
+        assert!(path.is_file());
+        assert!(close(std::slice::from_ref(&memory.name))[&memory.name]);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn it_reads_a_range_of_a_memory_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let directories = vec![dir.path().join(SUBDIRECTORY)];
+        let name = create(b"hello shared memory", &directories).unwrap();
+
+        let data = read(
+            &RpcSharedMemory {
+                name: name.clone(),
+                offset: 6,
+                count: 6,
+                r#type: RpcDataType::String as i32,
+            },
+            &directories,
+        )
+        .unwrap();
+        assert_eq!(data.data, Some(Data::String("shared".to_owned())));
+
+        assert!(read(
+            &RpcSharedMemory {
+                name: name.clone(),
+                offset: 6,
+                count: 100,
+                r#type: RpcDataType::String as i32,
+            },
+            &directories,
+        )
+        .is_err());
+
+        assert!(read(
+            &RpcSharedMemory {
+                name: name.clone(),
+                offset: i64::MAX,
+                count: 1,
+                r#type: RpcDataType::String as i32,
+            },
+            &directories,
+        )
+        .is_err());
+
+        close(&[name]);
+    }
+
+    #[test]
+    fn it_closes_all_memory_maps_in_the_given_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let directories = vec![dir.path().join(SUBDIRECTORY)];
+        let first = create(b"first", &directories).unwrap();
+        let second = create(b"second", &directories).unwrap();
+
+        close_all(&directories);
+
+        assert!(!directories[0].join(&first).exists());
+        assert!(!directories[0].join(&second).exists());
+        assert!(!MAPS.lock().unwrap().contains_key(&first));
+        assert!(!MAPS.lock().unwrap().contains_key(&second));
+    }
+
+    #[test]
+    fn it_falls_back_to_grpc_without_a_shared_memory_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        fs::write(&file, "not a directory").unwrap();
+
+        let res = write_outputs(
+            &FUNCTION,
+            InvocationResponse {
+                output_data: vec![ParameterBinding::new("blob", bytes(MIN_TRANSFER_LENGTH))],
+                ..Default::default()
+            },
+            &[file.join(SUBDIRECTORY)],
+        );
+
+        assert_eq!(res.output_data[0].data(), Some(&bytes(MIN_TRANSFER_LENGTH)));
+    }
+
+    #[test]
+    fn it_fails_to_read_a_missing_memory_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut req = InvocationRequest {
+            input_data: vec![ParameterBinding {
+                name: "blob".to_owned(),
+                rpc_data: Some(RpcData::RpcSharedMemory(RpcSharedMemory {
+                    name: "missing".to_owned(),
+                    count: 1,
+                    r#type: RpcDataType::Bytes as i32,
+                    ..Default::default()
+                })),
+            }],
+            ..Default::default()
+        };
+
+        let error = read_inputs(&mut req, &[dir.path().to_owned()]).unwrap_err();
+        assert!(error.contains("'blob'"));
+        assert!(error.contains("'missing'"));
+    }
+}
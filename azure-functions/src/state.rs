@@ -0,0 +1,142 @@
+//! Module for shared application state.
+use lazy_static::lazy_static;
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    fmt,
+    ops::Deref,
+    sync::{Arc, RwLock},
+};
+
+pub(crate) type StateMap = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+lazy_static! {
+    static ref STATE: RwLock<StateMap> = RwLock::new(HashMap::new());
+}
+
+// Makes the given state available to functions, replacing any state of the same type
+pub(crate) fn register(state: StateMap) {
+    STATE.write().unwrap().extend(state);
+}
+
+// Registers the given state until the returned guard is dropped
+//
+// The state registered before is restored when the guard is dropped.
+#[cfg(feature = "testing")]
+pub(crate) fn register_scoped(state: StateMap) -> ScopedState {
+    let mut registered = STATE.write().unwrap();
+    let previous = registered.clone();
+    registered.extend(state);
+    ScopedState(Some(previous))
+}
+
+#[cfg(feature = "testing")]
+pub(crate) struct ScopedState(Option<StateMap>);
+
+#[cfg(feature = "testing")]
+impl Drop for ScopedState {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            *STATE.write().unwrap() = previous;
+        }
+    }
+}
+
+/// Represents application state shared by every invocation of the worker.
+///
+/// State is registered with the worker by type using `WorkerBuilder::state` and is passed
+/// to Azure Functions that have a parameter of type `State<T>`.
+///
+/// The parameter type must be written as `State<T>`, `state::State<T>` or
+/// `azure_functions::state::State<T>`. An invocation fails if no state of type `T` has been
+/// registered.
+///
+/// # Examples
+///
+/// ```rust
+/// use azure_functions::{
+///     bindings::{HttpRequest, HttpResponse},
+///     func,
+///     state::State,
+/// };
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// pub struct Counter(AtomicUsize);
+///
+/// #[func]
+/// pub fn count(_req: HttpRequest, counter: State<Counter>) -> HttpResponse {
+///     format!("Count: {}", counter.0.fetch_add(1, Ordering::SeqCst) + 1).into()
+/// }
+/// ```
+pub struct State<T>(Arc<T>);
+
+impl<T: Send + Sync + 'static> State<T> {
+    /// Gets the state of type `T` registered with the worker.
+    ///
+    /// Returns None if no state of type `T` has been registered.
+    pub fn get() -> Option<Self> {
+        STATE
+            .read()
+            .unwrap()
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|s| s.downcast::<T>().ok())
+            .map(State)
+    }
+
+    #[doc(hidden)]
+    pub fn resolve() -> Result<Self, String> {
+        State::get().ok_or_else(|| {
+            format!(
+                "state of type `{}` has not been registered with the worker.",
+                type_name::<T>()
+            )
+        })
+    }
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        State(self.0.clone())
+    }
+}
+
+impl<T> Deref for State<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("State").field(&*self.0).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Registered(u32);
+    #[derive(Debug)]
+    struct Unregistered;
+
+    #[test]
+    fn it_resolves_registered_state() {
+        let mut state = StateMap::new();
+        state.insert(TypeId::of::<Registered>(), Arc::new(Registered(42)));
+        register(state);
+
+        assert_eq!(State::<Registered>::resolve().unwrap().0 .0, 42);
+        assert!(State::<Unregistered>::get().is_none());
+    }
+
+    #[test]
+    fn it_fails_to_resolve_unregistered_state() {
+        assert!(State::<Unregistered>::resolve()
+            .unwrap_err()
+            .ends_with("Unregistered` has not been registered with the worker."));
+    }
+}
@@ -0,0 +1,15 @@
+//! Module for testing Azure Functions.
+//!
+//! This module requires the `testing` feature.
+//!
+//! Builders for the trigger and input binding types are available with the `builder` function
+//! of each type (e.g. `HttpRequest::builder`).
+//!
+//! Use `invoke` to invoke an Azure Function in-process with the data from the builders.
+mod builder;
+mod host;
+mod invoke;
+
+pub use self::builder::*;
+pub use self::host::*;
+pub use self::invoke::*;
@@ -0,0 +1,11 @@
+use crate::rpc::TypedData;
+use std::collections::HashMap;
+
+/// Trait for builders of bindings used in testing.
+///
+/// A builder produces the binding data and trigger metadata that the Azure Functions Host would
+/// send to the worker for the binding.
+pub trait BindingBuilder {
+    /// Consumes the builder and returns the binding data and trigger metadata.
+    fn into_data(self) -> (TypedData, HashMap<String, TypedData>);
+}
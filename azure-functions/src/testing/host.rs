@@ -0,0 +1,672 @@
+use crate::{
+    codegen::Function,
+    logger,
+    registry::Registry,
+    rpc::{
+        function_rpc_server::{FunctionRpc, FunctionRpcServer},
+        rpc_log,
+        streaming_message::Content,
+        FunctionLoadRequest, FunctionLoadResponse, FunctionsMetadataRequest, InvocationRequest,
+        InvocationResponse, ParameterBinding, RpcFunctionMetadata, RpcLog, StreamingMessage,
+        TypedData, WorkerInitRequest, WorkerTerminate,
+    },
+    worker::{Worker, WORKER_INDEXING_CAPABILITY},
+};
+use futures::channel::oneshot;
+use std::{collections::HashMap, pin::Pin, time::Duration};
+use tokio::{
+    net::TcpListener,
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+};
+use tokio_stream::{
+    wrappers::{TcpListenerStream, UnboundedReceiverStream},
+    Stream, StreamExt,
+};
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+
+const HOST_VERSION: &str = "0.0.0-fake";
+const WORKER_ID: &str = "fake-worker";
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+type Outgoing = UnboundedSender<Result<StreamingMessage, Status>>;
+
+// Implements the gRPC service by handing each event stream to the fake host
+struct Service {
+    streams: UnboundedSender<(Streaming<StreamingMessage>, Outgoing)>,
+}
+
+#[tonic::async_trait]
+impl FunctionRpc for Service {
+    type EventStreamStream =
+        Pin<Box<dyn Stream<Item = Result<StreamingMessage, Status>> + Send + 'static>>;
+
+    async fn event_stream(
+        &self,
+        request: Request<Streaming<StreamingMessage>>,
+    ) -> Result<Response<Self::EventStreamStream>, Status> {
+        let (sender, receiver) = unbounded_channel();
+
+        self.streams
+            .send((request.into_inner(), sender))
+            .map_err(|_| Status::unavailable("the fake host is not accepting connections"))?;
+
+        Ok(Response::new(Box::pin(UnboundedReceiverStream::new(
+            receiver,
+        ))))
+    }
+}
+
+/// Represents the result of running a fake Azure Functions Host.
+#[derive(Debug, Default)]
+pub struct Session {
+    /// The capabilities advertised by the worker.
+    pub capabilities: HashMap<String, String>,
+    /// The function metadata reported by the worker when the fake host indexes functions through the worker.
+    pub metadata: Vec<RpcFunctionMetadata>,
+    /// The responses to the function load requests, in the order the functions were loaded.
+    pub load_responses: Vec<FunctionLoadResponse>,
+    /// The responses to the invocation requests, in the order the invocations were requested.
+    pub responses: Vec<InvocationResponse>,
+    /// The log messages sent by the worker.
+    pub logs: Vec<RpcLog>,
+}
+
+impl Session {
+    /// Gets the response for the given invocation identifier.
+    pub fn response(&self, invocation_id: &str) -> Option<&InvocationResponse> {
+        self.responses
+            .iter()
+            .find(|r| r.invocation_id == invocation_id)
+    }
+
+    /// Gets the log messages sent by the worker for the given invocation identifier.
+    pub fn logs_for<'a>(&'a self, invocation_id: &'a str) -> impl Iterator<Item = &'a RpcLog> {
+        self.logs
+            .iter()
+            .filter(move |l| l.invocation_id == invocation_id)
+    }
+}
+
+/// Represents an in-process Azure Functions Host for testing the Rust worker.
+///
+/// The fake host serves the Azure Functions Host gRPC protocol on a local port and runs the
+/// worker against it: the worker is initialized, every function is loaded, the requested
+/// invocations are sent and the worker is terminated once every invocation has completed.
+///
+/// Invocations are assigned the identifiers `1`, `2`, ... in the order they are requested;
+/// functions are loaded with their names as function identifiers.
+///
+/// Use `index_functions` to have the fake host request the function metadata from the worker
+/// and load the functions it reports, as a host using worker indexing would.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use azure_functions::{
+///     rpc::{status_result::Status, typed_data::Data, ParameterBinding, TypedData},
+///     testing::FakeHost,
+/// };
+///
+/// #[tokio::test]
+/// async fn it_greets() {
+///     let session = FakeHost::new(functions::EXPORTS)
+///         .invoke(
+///             "greet",
+///             vec![ParameterBinding::new(
+///                 "req",
+///                 TypedData {
+///                     data: Some(Data::Http(Default::default())),
+///                 },
+///             )],
+///             Default::default(),
+///         )
+///         .run()
+///         .await
+///         .unwrap();
+///
+///     let response = session.response("1").unwrap();
+///     assert_eq!(response.result.as_ref().unwrap().status, Status::Success as i32);
+/// }
+/// ```
+pub struct FakeHost {
+    functions: Vec<&'static Function>,
+    log_categories: HashMap<String, i32>,
+    capabilities: HashMap<String, String>,
+    max_message_length: Option<usize>,
+    worker_indexing: bool,
+    invocations: Vec<InvocationRequest>,
+}
+
+impl FakeHost {
+    /// Creates a new fake host for the given functions.
+    pub fn new(functions: &[&'static Function]) -> Self {
+        FakeHost {
+            functions: functions.to_vec(),
+            log_categories: HashMap::new(),
+            capabilities: HashMap::new(),
+            max_message_length: None,
+            worker_indexing: false,
+            invocations: Vec::new(),
+        }
+    }
+
+    /// Sets the log level of a log category sent to the worker upon initialization.
+    pub fn log_level(mut self, category: &str, level: rpc_log::Level) -> Self {
+        self.log_categories
+            .insert(category.to_owned(), level as i32);
+        self
+    }
+
+    /// Sets a capability of the host sent to the worker upon initialization.
+    pub fn capability(mut self, name: &str, value: &str) -> Self {
+        self.capabilities.insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Sets the maximum gRPC message length passed to the worker.
+    pub fn max_message_length(mut self, length: usize) -> Self {
+        self.max_message_length = Some(length);
+        self
+    }
+
+    /// Indexes the functions through the worker rather than from the functions given to the fake host.
+    pub fn index_functions(mut self) -> Self {
+        self.worker_indexing = true;
+        self
+    }
+
+    /// Requests an invocation of the given function.
+    pub fn invoke(
+        mut self,
+        function_name: &str,
+        input_data: Vec<ParameterBinding>,
+        trigger_metadata: HashMap<String, TypedData>,
+    ) -> Self {
+        self.invocations.push(InvocationRequest {
+            invocation_id: (self.invocations.len() + 1).to_string(),
+            function_id: function_name.to_owned(),
+            input_data,
+            trigger_metadata,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Runs the worker against the fake host.
+    ///
+    /// This must be called from within a Tokio runtime.
+    pub async fn run(self) -> Result<Session, String> {
+        // The worker's logger is global, so only one fake host may run at a time
+        let _lock = logger::SENDER_LOCK.lock().await;
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| format!("failed to bind the fake host: {}", e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("failed to get the fake host address: {}", e))?;
+
+        let (streams_sender, mut streams) = unbounded_channel();
+        let (shutdown_sender, shutdown) = oneshot::channel::<()>();
+
+        let server = tokio::spawn(
+            Server::builder()
+                .add_service(FunctionRpcServer::new(Service {
+                    streams: streams_sender,
+                }))
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                    shutdown.await.unwrap_or(());
+                }),
+        );
+
+        let worker = tokio::spawn(Worker::connect(
+            format!("http://{}", addr).parse().unwrap(),
+            WORKER_ID,
+            self.max_message_length,
+            Registry::new(&self.functions),
+        ));
+
+        let result = tokio::time::timeout(SESSION_TIMEOUT, async {
+            let (incoming, outgoing) = streams
+                .recv()
+                .await
+                .ok_or_else(|| "the worker did not connect to the fake host".to_string())?;
+
+            self.drive(incoming, outgoing).await
+        })
+        .await
+        .unwrap_or_else(|_| Err("timed out waiting for the worker".to_string()));
+
+        // Never leave the worker running past the session, even when the session failed
+        let result = match result {
+            Ok(session) => match worker.await {
+                Ok(Ok(())) => Ok(session),
+                Ok(Err(e)) => Err(format!("the worker failed: {}", e)),
+                Err(e) => Err(format!("the worker failed: {}", e)),
+            },
+            Err(e) => {
+                worker.abort();
+                worker.await.ok();
+                Err(e)
+            }
+        };
+
+        shutdown_sender.send(()).unwrap_or(());
+        let served = match server.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(format!("fake host server failed: {}", e)),
+            Err(e) => Err(format!("fake host server failed: {}", e)),
+        };
+
+        result.and_then(|session| served.map(|_| session))
+    }
+
+    async fn drive(
+        &self,
+        mut incoming: Streaming<StreamingMessage>,
+        outgoing: Outgoing,
+    ) -> Result<Session, String> {
+        let mut session = Session::default();
+
+        match FakeHost::receive(&mut incoming, &mut session).await? {
+            Content::StartStream(_) => {}
+            content => return Err(format!("expected a start stream message: {:?}", content)),
+        };
+
+        FakeHost::send(
+            &outgoing,
+            Content::WorkerInitRequest(WorkerInitRequest {
+                host_version: HOST_VERSION.to_owned(),
+                log_categories: self.log_categories.clone(),
+                capabilities: self.capabilities.clone(),
+                ..Default::default()
+            }),
+        )?;
+
+        match FakeHost::receive(&mut incoming, &mut session).await? {
+            Content::WorkerInitResponse(res) => session.capabilities = res.capabilities,
+            content => {
+                return Err(format!(
+                    "expected a worker init response message: {:?}",
+                    content
+                ))
+            }
+        };
+
+        let metadata = if self.worker_indexing {
+            if session
+                .capabilities
+                .get(WORKER_INDEXING_CAPABILITY)
+                .map(String::as_str)
+                != Some("true")
+            {
+                return Err("the worker does not support worker indexing".to_string());
+            }
+
+            FakeHost::send(
+                &outgoing,
+                Content::FunctionsMetadataRequest(FunctionsMetadataRequest {
+                    function_app_directory: ".".to_owned(),
+                }),
+            )?;
+
+            match FakeHost::receive(&mut incoming, &mut session).await? {
+                Content::FunctionMetadataResponse(res) => {
+                    session.metadata = res.function_metadata_results;
+                    session.metadata.clone()
+                }
+                content => {
+                    return Err(format!(
+                        "expected a function metadata response message: {:?}",
+                        content
+                    ))
+                }
+            }
+        } else {
+            self.functions
+                .iter()
+                .map(|func| RpcFunctionMetadata {
+                    name: func.name.to_string(),
+                    function_id: func.name.to_string(),
+                    ..Default::default()
+                })
+                .collect()
+        };
+
+        for metadata in metadata {
+            FakeHost::send(
+                &outgoing,
+                Content::FunctionLoadRequest(FunctionLoadRequest {
+                    function_id: metadata.function_id.clone(),
+                    metadata: Some(metadata),
+                    ..Default::default()
+                }),
+            )?;
+
+            match FakeHost::receive(&mut incoming, &mut session).await? {
+                Content::FunctionLoadResponse(res) => session.load_responses.push(res),
+                content => {
+                    return Err(format!(
+                        "expected a function load response message: {:?}",
+                        content
+                    ))
+                }
+            };
+        }
+
+        for req in &self.invocations {
+            FakeHost::send(&outgoing, Content::InvocationRequest(req.clone()))?;
+        }
+
+        let mut responses = HashMap::new();
+        while responses.len() < self.invocations.len() {
+            match FakeHost::receive(&mut incoming, &mut session).await? {
+                Content::InvocationResponse(res) => {
+                    responses.insert(res.invocation_id.clone(), res);
+                }
+                content => {
+                    return Err(format!(
+                        "expected an invocation response message: {:?}",
+                        content
+                    ))
+                }
+            };
+        }
+
+        session.responses = self
+            .invocations
+            .iter()
+            .filter_map(|req| responses.remove(&req.invocation_id))
+            .collect();
+
+        FakeHost::send(
+            &outgoing,
+            Content::WorkerTerminate(WorkerTerminate {
+                grace_period: Some(prost_types::Duration {
+                    seconds: SHUTDOWN_GRACE_PERIOD.as_secs() as i64,
+                    nanos: 0,
+                }),
+            }),
+        )?;
+
+        // The worker closes its stream once it has flushed its remaining messages
+        while let Some(message) = incoming.next().await {
+            match message
+                .map_err(|e| format!("failed to read from the worker: {}", e))?
+                .content
+            {
+                Some(Content::RpcLog(log)) => session.logs.push(log),
+                content => {
+                    return Err(format!(
+                        "unexpected message from the worker during shutdown: {:?}",
+                        content
+                    ))
+                }
+            };
+        }
+
+        Ok(session)
+    }
+
+    fn send(outgoing: &Outgoing, content: Content) -> Result<(), String> {
+        outgoing
+            .send(Ok(StreamingMessage {
+                content: Some(content),
+                ..Default::default()
+            }))
+            .map_err(|_| "the worker disconnected from the fake host".to_string())
+    }
+
+    // Receives the next message from the worker that is not a log message
+    async fn receive(
+        incoming: &mut Streaming<StreamingMessage>,
+        session: &mut Session,
+    ) -> Result<Content, String> {
+        loop {
+            let message = incoming
+                .next()
+                .await
+                .ok_or_else(|| "the worker disconnected from the fake host".to_string())?
+                .map_err(|e| format!("failed to read from the worker: {}", e))?;
+
+            match message.content {
+                Some(Content::RpcLog(log)) => session.logs.push(log),
+                Some(content) => return Ok(content),
+                None => return Err("received an empty message from the worker".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bindings::QueueMessage,
+        codegen::{InvocationFuture, InvokerFn},
+        fixtures,
+        rpc::{status_result, typed_data::Data, CollectionString, StatusResult},
+        FromVec,
+    };
+
+    // Creates a successful response for the invocation with the given return value
+    fn succeed(invocation_id: String, return_value: Option<TypedData>) -> InvocationResponse {
+        InvocationResponse {
+            invocation_id,
+            return_value,
+            result: Some(StatusResult {
+                status: status_result::Status::Success as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn greet(req: InvocationRequest) -> InvocationFuture {
+        Box::pin(async move {
+            log::info!("Hello from invocation {}!", req.invocation_id);
+            succeed(req.invocation_id, None)
+        })
+    }
+
+    // Returns the input payload as the return value
+    fn echo(req: InvocationRequest) -> InvocationFuture {
+        Box::pin(async move {
+            let input = req
+                .input_data
+                .into_iter()
+                .next()
+                .and_then(ParameterBinding::into_data);
+            succeed(req.invocation_id, input)
+        })
+    }
+
+    // Returns a batch of queue messages
+    fn batch(req: InvocationRequest) -> InvocationFuture {
+        Box::pin(async move {
+            let messages = vec![QueueMessage::from("hello"), QueueMessage::from("world")];
+            succeed(req.invocation_id, Some(TypedData::from_vec(messages)))
+        })
+    }
+
+    static GREET_FUNCTION: Function =
+        fixtures::invoked_function("greet", InvokerFn::Async(Some(greet)));
+    static ECHO_FUNCTION: Function =
+        fixtures::invoked_function("echo", InvokerFn::Async(Some(echo)));
+    static BATCH_FUNCTION: Function =
+        fixtures::invoked_function("batch", InvokerFn::Async(Some(batch)));
+
+    fn payload(len: usize) -> Vec<ParameterBinding> {
+        vec![ParameterBinding::new(
+            "payload",
+            TypedData {
+                data: Some(Data::Bytes((0..len).map(|i| i as u8).collect())),
+            },
+        )]
+    }
+
+    #[tokio::test]
+    async fn it_round_trips_large_payloads() {
+        const LENGTH: usize = 12 * 1024 * 1024;
+
+        let session = FakeHost::new(&[&ECHO_FUNCTION])
+            .max_message_length(64 * 1024 * 1024)
+            .invoke("echo", payload(LENGTH), HashMap::new())
+            .run()
+            .await
+            .unwrap();
+
+        let response = session.response("1").unwrap();
+        assert_eq!(
+            response.result.as_ref().unwrap().status,
+            status_result::Status::Success as i32
+        );
+
+        match response.return_value.as_ref().and_then(|v| v.data.as_ref()) {
+            Some(Data::Bytes(bytes)) => {
+                assert_eq!(bytes.len(), LENGTH);
+                assert!(bytes.iter().enumerate().all(|(i, b)| *b == i as u8));
+            }
+            _ => panic!("expected a bytes return value"),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_fails_responses_exceeding_the_maximum_message_length() {
+        let session = FakeHost::new(&[&ECHO_FUNCTION])
+            .max_message_length(1024 * 1024)
+            .invoke("echo", payload(2 * 1024 * 1024), HashMap::new())
+            .run()
+            .await
+            .unwrap();
+
+        let response = session.response("1").unwrap();
+        assert!(response.return_value.is_none());
+
+        let result = response.result.as_ref().unwrap();
+        assert_eq!(result.status, status_result::Status::Failure as i32);
+        assert!(result.result.contains("Azure Function 'echo'"));
+        assert!(result.result.contains("'$return'"));
+    }
+
+    #[tokio::test]
+    async fn it_runs_the_worker() {
+        let session = FakeHost::new(&[&GREET_FUNCTION])
+            .invoke("greet", Vec::new(), HashMap::new())
+            .invoke("missing", Vec::new(), HashMap::new())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(session.load_responses.len(), 1);
+        assert_eq!(
+            session.load_responses[0].result.as_ref().unwrap().status,
+            status_result::Status::Success as i32
+        );
+
+        assert_eq!(session.responses.len(), 2);
+        assert_eq!(
+            session
+                .response("1")
+                .unwrap()
+                .result
+                .as_ref()
+                .unwrap()
+                .status,
+            status_result::Status::Success as i32
+        );
+        assert_eq!(
+            session
+                .response("2")
+                .unwrap()
+                .result
+                .as_ref()
+                .unwrap()
+                .status,
+            status_result::Status::Failure as i32
+        );
+
+        assert!(session
+            .logs_for("1")
+            .any(|l| l.message == "Hello from invocation 1!"));
+    }
+
+    #[tokio::test]
+    async fn it_indexes_functions_through_the_worker() {
+        let session = FakeHost::new(&[&GREET_FUNCTION, &ECHO_FUNCTION])
+            .index_functions()
+            .invoke("greet", Vec::new(), HashMap::new())
+            .run()
+            .await
+            .unwrap();
+
+        let names: Vec<_> = session.metadata.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["echo", "greet"]);
+        assert!(session
+            .metadata
+            .iter()
+            .all(|m| m.function_id == m.name && m.language == "Rust"));
+
+        assert_eq!(session.load_responses.len(), 2);
+        assert!(session
+            .load_responses
+            .iter()
+            .all(|r| r.result.as_ref().unwrap().status == status_result::Status::Success as i32));
+
+        assert_eq!(
+            session
+                .response("1")
+                .unwrap()
+                .result
+                .as_ref()
+                .unwrap()
+                .status,
+            status_result::Status::Success as i32
+        );
+    }
+
+    #[tokio::test]
+    async fn it_negotiates_typed_data_collections() {
+        let session = FakeHost::new(&[&BATCH_FUNCTION])
+            .invoke("batch", Vec::new(), HashMap::new())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            session.capabilities.get("TypedDataCollection").unwrap(),
+            "true"
+        );
+        assert_eq!(
+            session
+                .response("1")
+                .unwrap()
+                .return_value
+                .as_ref()
+                .unwrap()
+                .data,
+            Some(Data::Json(r#"["hello","world"]"#.to_owned()))
+        );
+
+        let session = FakeHost::new(&[&BATCH_FUNCTION])
+            .capability("TypedDataCollection", "true")
+            .invoke("batch", Vec::new(), HashMap::new())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            session
+                .response("1")
+                .unwrap()
+                .return_value
+                .as_ref()
+                .unwrap()
+                .data,
+            Some(Data::CollectionString(CollectionString {
+                string: vec!["hello".to_owned(), "world".to_owned()],
+            }))
+        );
+    }
+}
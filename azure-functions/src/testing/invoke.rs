@@ -0,0 +1,427 @@
+use crate::{
+    codegen::Function,
+    registry::Registry,
+    rpc::{
+        status_result::Status, streaming_message::Content, typed_data::Data, InvocationRequest,
+        InvocationResponse, ParameterBinding, RpcException, TypedData,
+    },
+    state::{self, StateMap},
+    testing::BindingBuilder,
+    worker::{Capabilities, Invocations, Worker},
+};
+use azure_functions_shared::util::to_camel_case;
+use futures::{channel::mpsc::unbounded, lock::Mutex, StreamExt};
+use lazy_static::lazy_static;
+use serde_json::{from_str, Value};
+use std::{any::TypeId, collections::HashMap, sync::Arc};
+
+const INVOCATION_ID: &str = "1";
+
+lazy_static! {
+    // State is registered for the process, so only one invocation may run at a time
+    static ref RUN_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Creates an invocation of the function with the given name.
+///
+/// The function is looked up in the given functions (typically the `EXPORTS` of the
+/// application) and is invoked in-process exactly as the worker invokes it for the Azure
+/// Functions Host, including its middleware, concurrency limits and timeout.
+///
+/// This function requires the `testing` feature.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use azure_functions::{
+///     bindings::{HttpRequest, HttpResponse, QueueMessage},
+///     testing,
+/// };
+///
+/// #[tokio::test(flavor = "multi_thread")]
+/// async fn it_enqueues_the_name() {
+///     let result = testing::invoke(functions::EXPORTS, "greet")
+///         .binding("req", HttpRequest::builder().query_param("name", "Peter"))
+///         .run()
+///         .await
+///         .unwrap();
+///
+///     assert!(result.is_success());
+///
+///     let response: HttpResponse = result.return_value().unwrap();
+///     assert_eq!(response.body().as_str().unwrap(), "Hello, Peter!");
+///
+///     let message: QueueMessage = result.output("queue").unwrap();
+///     assert_eq!(message.as_str().unwrap(), "Peter");
+/// }
+/// ```
+pub fn invoke(functions: &[&'static Function], name: &str) -> Invocation {
+    let mut registry = Registry::new(functions);
+
+    Invocation {
+        function: if registry.register(name, name) {
+            Ok(registry.get(name).unwrap())
+        } else {
+            Err(format!("Azure Function '{}' does not exist.", name))
+        },
+        input_data: Vec::new(),
+        trigger_metadata: HashMap::new(),
+        state: StateMap::new(),
+    }
+}
+
+/// Represents an in-process invocation of an Azure Function.
+///
+/// Use `testing::invoke` to create an invocation.
+pub struct Invocation {
+    function: Result<&'static Function, String>,
+    input_data: Vec<ParameterBinding>,
+    trigger_metadata: HashMap<String, TypedData>,
+    state: StateMap,
+}
+
+impl Invocation {
+    /// Adds the data for the binding with the given name.
+    ///
+    /// The name is the name of the binding's function parameter; the trigger metadata produced
+    /// by the builder is used when the binding is the function's trigger.
+    pub fn binding<B: BindingBuilder>(mut self, name: &str, builder: B) -> Self {
+        let function = match self.function {
+            Ok(function) => function,
+            Err(_) => return self,
+        };
+
+        let name = to_camel_case(name);
+        let binding = match function
+            .bindings
+            .iter()
+            .find(|b| b.name() == Some(name.as_str()))
+        {
+            Some(binding) => binding,
+            None => {
+                self.function = Err(format!(
+                    "Azure Function '{}' does not have a binding named '{}'.",
+                    function.name, name
+                ));
+                return self;
+            }
+        };
+
+        let (data, metadata) = builder.into_data();
+        if binding.is_trigger() {
+            self.trigger_metadata = metadata;
+        }
+
+        self.input_data.push(ParameterBinding::new(name, data));
+        self
+    }
+
+    /// Registers state that is passed to a parameter of type `State<T>`.
+    ///
+    /// The state is registered only while the invocation runs; invocations that run
+    /// concurrently wait for each other so that they do not observe each other's state.
+    pub fn state<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.state.insert(TypeId::of::<T>(), Arc::new(value));
+        self
+    }
+
+    /// Runs the invocation and returns its result.
+    ///
+    /// The function runs with an invocation context whose invocation identifier is `1`.
+    ///
+    /// A panic in the function fails the invocation, as it does when running in the worker.
+    ///
+    /// This must be called from within a multi-threaded Tokio runtime.
+    pub async fn run(self) -> Result<InvocationResult, String> {
+        let function = self.function?;
+
+        if function.invoker.is_none() {
+            return Err(format!(
+                "Azure Function '{}' has no invoker.",
+                function.name
+            ));
+        }
+
+        let _lock = RUN_LOCK.lock().await;
+        let _state = state::register_scoped(self.state);
+
+        let req = InvocationRequest {
+            invocation_id: INVOCATION_ID.to_owned(),
+            function_id: function.name.to_string(),
+            input_data: self.input_data,
+            trigger_metadata: self.trigger_metadata,
+            ..Default::default()
+        };
+
+        let (sender, mut receiver) = unbounded();
+        Worker::invoke_function(
+            function,
+            Invocations::default(),
+            Capabilities::default(),
+            sender,
+            req,
+        );
+
+        match receiver.next().await.and_then(|m| m.content) {
+            Some(Content::InvocationResponse(res)) => Ok(InvocationResult(res)),
+            _ => Err(format!(
+                "Azure Function '{}' did not respond to the invocation.",
+                function.name
+            )),
+        }
+    }
+}
+
+/// Represents the result of an in-process invocation of an Azure Function.
+#[derive(Debug)]
+pub struct InvocationResult(InvocationResponse);
+
+impl InvocationResult {
+    /// Gets the status of the invocation.
+    pub fn status(&self) -> Status {
+        self.0
+            .result
+            .as_ref()
+            .and_then(|r| Status::from_i32(r.status))
+            .unwrap_or(Status::Failure)
+    }
+
+    /// Gets whether or not the invocation succeeded.
+    pub fn is_success(&self) -> bool {
+        self.status() == Status::Success
+    }
+
+    /// Gets the error message of a failed invocation.
+    pub fn error(&self) -> Option<&str> {
+        if self.is_success() {
+            return None;
+        }
+
+        self.0.result.as_ref().map(|r| r.result.as_str())
+    }
+
+    /// Gets the exception of a failed invocation.
+    pub fn exception(&self) -> Option<&RpcException> {
+        self.0.result.as_ref().and_then(|r| r.exception.as_ref())
+    }
+
+    /// Gets the value of the output binding with the given name.
+    ///
+    /// Output bindings returned in a tuple are named `output1`, `output2`, ... unless the
+    /// function names them with a `binding` attribute.
+    pub fn output<T: From<TypedData>>(&self, name: &str) -> Option<T> {
+        self.output_data(name).cloned().map(Into::into)
+    }
+
+    /// Gets the values of an output binding with the given name that is a `Vec`.
+    ///
+    /// Returns an error if the output binding is not a JSON array of values.
+    pub fn outputs<T: From<TypedData>>(&self, name: &str) -> Result<Vec<T>, String> {
+        let values: Vec<Value> = match self.output_data(name).map(|d| &d.data) {
+            Some(Some(Data::Json(s))) => from_str(s).map_err(|e| {
+                format!(
+                    "output binding '{}' is not a JSON array of values: {}",
+                    name, e
+                )
+            })?,
+            Some(_) => return Err(format!("output binding '{}' is not JSON data", name)),
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(values
+            .into_iter()
+            .map(|v| {
+                TypedData {
+                    data: Some(match v {
+                        Value::String(s) => Data::String(s),
+                        v => Data::Json(v.to_string()),
+                    }),
+                }
+                .into()
+            })
+            .collect())
+    }
+
+    /// Gets the data of the output binding with the given name.
+    pub fn output_data(&self, name: &str) -> Option<&TypedData> {
+        let name = to_camel_case(name);
+
+        self.0
+            .output_data
+            .iter()
+            .find(|b| b.name == name)
+            .and_then(ParameterBinding::data)
+    }
+
+    /// Gets the return value of the function (the `$return` binding).
+    pub fn return_value<T: From<TypedData>>(&self) -> Option<T> {
+        self.0.return_value.clone().map(Into::into)
+    }
+
+    /// Gets the invocation response as it would be sent to the Azure Functions Host.
+    pub fn response(&self) -> &InvocationResponse {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bindings::{HttpRequest, HttpResponse, QueueMessage},
+        context::Context,
+        func,
+        state::State,
+        trace::Layer,
+    };
+    use tracing::{info_span, Instrument};
+    use tracing_subscriber::prelude::*;
+
+    #[func]
+    #[binding(name = "output1", queue_name = "example")]
+    #[binding(name = "output2", queue_name = "done")]
+    pub fn greet(req: HttpRequest) -> (HttpResponse, Vec<QueueMessage>, QueueMessage) {
+        let name = req.query_params().get("name").unwrap().clone();
+
+        (
+            format!(
+                "Hello from {}, {}!",
+                Context::current().unwrap().function_name(),
+                name
+            )
+            .into(),
+            vec![name.as_str().into(), "bye".into()],
+            "done".into(),
+        )
+    }
+
+    pub struct Greeting(&'static str);
+
+    #[func]
+    pub fn greet_with_state(_req: HttpRequest, greeting: State<Greeting>) -> HttpResponse {
+        greeting.0.into()
+    }
+
+    #[func(timeout = "00:00:01")]
+    pub async fn hang(_req: HttpRequest) -> HttpResponse {
+        futures::future::pending()
+            .instrument(info_span!("query"))
+            .await
+    }
+
+    #[func]
+    pub fn fail(_req: HttpRequest) -> HttpResponse {
+        panic!("failed to greet");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn it_invokes_a_function() {
+        let result = invoke(&[&GREET_FUNCTION], "greet")
+            .binding("req", HttpRequest::builder().query_param("name", "Peter"))
+            .run()
+            .await
+            .unwrap();
+
+        assert!(result.is_success());
+        assert!(result.error().is_none());
+
+        let response: HttpResponse = result.return_value().unwrap();
+        assert_eq!(
+            response.body().as_str().unwrap(),
+            "Hello from greet, Peter!"
+        );
+
+        let messages: Vec<QueueMessage> = result.outputs("output1").unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].as_str().unwrap(), "Peter");
+        assert_eq!(messages[1].as_str().unwrap(), "bye");
+
+        let message: QueueMessage = result.output("output2").unwrap();
+        assert_eq!(message.as_str().unwrap(), "done");
+        assert_eq!(
+            result.outputs::<QueueMessage>("output2").unwrap_err(),
+            "output binding 'output2' is not JSON data"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn it_scopes_state_to_the_invocation() {
+        let result = invoke(&[&GREET_WITH_STATE_FUNCTION], "greet_with_state")
+            .binding("req", HttpRequest::builder())
+            .state(Greeting("Hello!"))
+            .run()
+            .await
+            .unwrap();
+
+        let response: HttpResponse = result.return_value().unwrap();
+        assert_eq!(response.body().as_str().unwrap(), "Hello!");
+        assert!(State::<Greeting>::get().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn it_fails_the_invocation_for_unregistered_state() {
+        let result = invoke(&[&GREET_WITH_STATE_FUNCTION], "greet_with_state")
+            .binding("req", HttpRequest::builder())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(result.status(), Status::Failure);
+        assert!(result
+            .error()
+            .unwrap()
+            .ends_with("Greeting` has not been registered with the worker."));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn it_fails_the_invocation_for_a_panic() {
+        let result = invoke(&[&FAIL_FUNCTION], "fail")
+            .binding("req", HttpRequest::builder())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(result.status(), Status::Failure);
+    }
+
+    #[tokio::test]
+    async fn it_fails_the_invocation_when_it_times_out() {
+        // The invocation runs on this thread, so it uses the subscriber
+        let _default =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(Layer::new()));
+
+        let result = invoke(&[&HANG_FUNCTION], "hang")
+            .binding("req", HttpRequest::builder())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(result.status(), Status::Failure);
+
+        let exception = result.exception().unwrap();
+        assert_eq!(
+            exception.message,
+            "Azure Function 'hang' timed out after 1 seconds."
+        );
+        assert!(
+            exception
+                .stack_trace
+                .starts_with("while in span 'query' at "),
+            "{}",
+            exception.stack_trace
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn it_fails_for_an_unknown_binding() {
+        let result = invoke(&[&GREET_FUNCTION], "greet")
+            .binding("request", HttpRequest::builder())
+            .run()
+            .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Azure Function 'greet' does not have a binding named 'request'."
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Module for the `tracing` integration.
+use crate::{
+    logger::{self, EVENT_ID_KEY},
+    rpc::{rpc_log, RpcException, RpcLog},
+    util::to_exception,
+};
+use serde_json::{Map, Number, Value};
+use std::{error::Error, fmt};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Span, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Registry};
+
+// The name of the span the worker opens for each invocation.
+pub(crate) const INVOCATION_SPAN: &str = "invocation";
+const INVOCATION_ID_FIELD: &str = "invocation_id";
+const MESSAGE_FIELD: &str = "message";
+
+/// Represents a `tracing` layer that sends events to the Azure Functions Host.
+///
+/// The worker opens an `invocation` span around every invocation with the `function`,
+/// `invocation_id` and `trigger` fields.
+///
+/// The fields of an event and of the spans it occurs in are sent as the properties of the log
+/// message. As with the `log` crate integration, an `event_id` field sets the identifier of the
+/// log event and an error field is sent as the exception of the
+/// log message.
+///
+/// Events are filtered by the log levels configured for the host.
+///
+/// The layer also remembers the last span entered within each invocation; when an invocation
+/// times out, the span is logged to help identify the await point that did not complete.
+/// The span is only remembered when the layer is added to a `tracing_subscriber::Registry`,
+/// as in the example below.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tracing_subscriber::prelude::*;
+///
+/// pub fn main() {
+///     tracing_subscriber::registry()
+///         .with(azure_functions::trace::Layer::new())
+///         .init();
+///
+///     azure_functions::worker_main(std::env::args(), functions::EXPORTS);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Layer {
+    _priv: (),
+}
+
+impl Layer {
+    /// Creates a new layer.
+    pub fn new() -> Self {
+        Layer::default()
+    }
+}
+
+// The fields recorded for a span.
+struct SpanFields(Map<String, Value>);
+
+// The last span entered within an invocation, recorded on the invocation span.
+struct ActiveSpan(String);
+
+// Gets a description of the last span entered within the given invocation span
+//
+// This requires the span to have been created by a subscriber that is a `Registry` with this
+// layer; `None` is returned for any other subscriber.
+pub(crate) fn active_span(invocation: &Span) -> Option<String> {
+    invocation
+        .with_subscriber(|(id, dispatch)| {
+            dispatch
+                .downcast_ref::<Registry>()?
+                .span(id)?
+                .extensions()
+                .get::<ActiveSpan>()
+                .map(|s| s.0.clone())
+        })
+        .flatten()
+}
+
+impl<S> tracing_subscriber::Layer<S> for Layer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes, id: &Id, ctx: Context<S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+
+        span.extensions_mut().insert(SpanFields(fields.values));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record, ctx: Context<S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+
+        let mut extensions = span.extensions_mut();
+        if let Some(existing) = extensions.get_mut::<SpanFields>() {
+            existing.0.extend(fields.values);
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        if span.name() == INVOCATION_SPAN {
+            return;
+        }
+
+        if let Some(invocation) = span.scope().skip(1).find(|s| s.name() == INVOCATION_SPAN) {
+            let metadata = span.metadata();
+            let description = match (metadata.file(), metadata.line()) {
+                (Some(file), Some(line)) => format!("'{}' at {}:{}", span.name(), file, line),
+                _ => format!("'{}'", span.name()),
+            };
+
+            invocation.extensions_mut().replace(ActiveSpan(description));
+        }
+    }
+
+    fn on_event(&self, event: &Event, ctx: Context<S>) {
+        let metadata = event.metadata();
+        if !logger::is_enabled(convert_level(*metadata.level()), metadata.target()) {
+            return;
+        }
+
+        // Span fields are recorded from the root so that inner spans override outer spans
+        let mut properties = Map::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(fields) = span.extensions().get::<SpanFields>() {
+                    properties.extend(fields.0.clone());
+                }
+            }
+        }
+
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        properties.extend(fields.values);
+
+        let invocation_id = match properties.get(INVOCATION_ID_FIELD) {
+            Some(Value::String(id)) => id.clone(),
+            _ => crate::context::CURRENT
+                .try_with(|c| c.invocation_id.clone())
+                .unwrap_or_default(),
+        };
+
+        let event_id = match properties.remove(EVENT_ID_KEY) {
+            Some(Value::String(id)) => id,
+            Some(id) => id.to_string(),
+            None => String::new(),
+        };
+
+        let event = RpcLog {
+            invocation_id,
+            level: match *metadata.level() {
+                Level::TRACE => rpc_log::Level::Trace,
+                Level::DEBUG => rpc_log::Level::Debug,
+                Level::INFO => rpc_log::Level::Information,
+                Level::WARN => rpc_log::Level::Warning,
+                Level::ERROR => rpc_log::Level::Error,
+            } as i32,
+            message: fields.message.unwrap_or_default(),
+            category: metadata.target().to_owned(),
+            event_id,
+            exception: fields.exception.map(|mut e| {
+                e.source = metadata.target().to_owned();
+                e
+            }),
+            properties: if properties.is_empty() {
+                String::new()
+            } else {
+                serde_json::to_string(&properties).unwrap()
+            },
+            ..Default::default()
+        };
+
+        logger::send(event);
+    }
+}
+
+fn convert_level(level: Level) -> log::Level {
+    match level {
+        Level::TRACE => log::Level::Trace,
+        Level::DEBUG => log::Level::Debug,
+        Level::INFO => log::Level::Info,
+        Level::WARN => log::Level::Warn,
+        Level::ERROR => log::Level::Error,
+    }
+}
+
+// Collects the fields of a span or event.
+#[derive(Default)]
+struct Fields {
+    values: Map<String, Value>,
+    message: Option<String>,
+    exception: Option<RpcException>,
+}
+
+impl Fields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        self.values.insert(field.name().to_owned(), value);
+    }
+}
+
+impl Visit for Fields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        match Number::from_f64(value) {
+            Some(n) => self.insert(field, Value::Number(n)),
+            None => self.insert(field, Value::String(value.to_string())),
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::Number(value.into()));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::Number(value.into()));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == MESSAGE_FIELD {
+            self.message = Some(value.to_owned());
+            return;
+        }
+
+        self.insert(field, Value::String(value.to_owned()));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn Error + 'static)) {
+        if self.exception.is_none() {
+            self.exception = Some(to_exception(value, ""));
+        }
+
+        self.insert(field, Value::String(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == MESSAGE_FIELD {
+            self.message = Some(format!("{:?}", value));
+            return;
+        }
+
+        self.insert(field, Value::String(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::streaming_message::Content;
+    use futures::{channel::mpsc::unbounded, StreamExt};
+    use tracing::{error, info, info_span};
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn it_records_the_last_span_entered_within_an_invocation() {
+        let subscriber = tracing_subscriber::registry().with(Layer::new());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let invocation = info_span!(INVOCATION_SPAN, function = "greet");
+            let _entered = invocation.enter();
+
+            assert!(active_span(&invocation).is_none());
+
+            info_span!("query").in_scope(|| {});
+
+            let active = active_span(&invocation).unwrap();
+            assert!(active.starts_with("'query' at "), "{}", active);
+        });
+    }
+
+    #[test]
+    fn it_sends_events_with_span_fields() {
+        let _lock = futures::executor::block_on(logger::SENDER_LOCK.lock());
+
+        let (sender, mut receiver) = unbounded();
+        logger::set_sender(sender);
+
+        let subscriber = tracing_subscriber::registry().with(Layer::new());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = info_span!(
+                INVOCATION_SPAN,
+                function = "greet",
+                invocation_id = "1234",
+                trigger = "httpTrigger"
+            );
+            let _entered = span.enter();
+
+            info!(
+                event_id = "greeted",
+                name = "Peter",
+                count = 3,
+                "Hello {}!",
+                "Peter"
+            );
+
+            let error = std::io::Error::other("oh no");
+            error!(error = &error as &(dyn Error + 'static), "failed");
+        });
+
+        let message = futures::executor::block_on(receiver.next()).unwrap();
+        let log = match message.content {
+            Some(Content::RpcLog(log)) => log,
+            _ => panic!("expected a log message"),
+        };
+
+        assert_eq!(log.invocation_id, "1234");
+        assert_eq!(log.message, "Hello Peter!");
+        assert_eq!(log.level, rpc_log::Level::Information as i32);
+        assert_eq!(log.event_id, "greeted");
+        assert_eq!(log.category, module_path!());
+        assert_eq!(
+            log.properties,
+            r#"{"count":3,"function":"greet","invocation_id":"1234","name":"Peter","trigger":"httpTrigger"}"#
+        );
+        assert!(log.exception.is_none());
+
+        let message = futures::executor::block_on(receiver.next()).unwrap();
+        let log = match message.content {
+            Some(Content::RpcLog(log)) => log,
+            _ => panic!("expected a log message"),
+        };
+
+        assert_eq!(log.message, "failed");
+        assert_eq!(log.level, rpc_log::Level::Error as i32);
+        assert!(log.event_id.is_empty());
+
+        let exception = log.exception.unwrap();
+        assert_eq!(exception.message, "oh no");
+        assert_eq!(exception.source, module_path!());
+    }
+}
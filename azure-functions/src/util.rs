@@ -1,7 +1,12 @@
-use crate::rpc::{typed_data::Data, TypedData};
+use crate::{
+    context::CURRENT,
+    rpc::{typed_data::Data, CollectionBytes, CollectionString, RpcException, TypedData},
+};
 use chrono::{DateTime, FixedOffset, Utc};
 use serde::{de::Error, de::IntoDeserializer, Deserialize, Deserializer};
 use serde_json::from_str;
+use std::error::Error as StdError;
+use std::fmt::Write;
 use std::str::{from_utf8, FromStr};
 
 pub fn convert_from<'a, T>(data: &'a TypedData) -> Option<T>
@@ -37,6 +42,97 @@ where
     }
 }
 
+pub fn to_exception(error: &(dyn StdError + 'static), source: &str) -> RpcException {
+    // The error's chain of sources is reported in place of a stack trace
+    let mut stack_trace = String::new();
+    let mut cause = error.source();
+    while let Some(e) = cause {
+        writeln!(stack_trace, "Caused by: {}", e).unwrap();
+        cause = e.source();
+    }
+
+    RpcException {
+        source: source.to_owned(),
+        stack_trace,
+        message: error.to_string(),
+        ..Default::default()
+    }
+}
+
+// Converts the values of an output binding to a typed data collection
+//
+// Returns the values if the host does not accept collections or if the values are not all strings or all bytes.
+pub fn into_collection(values: Vec<TypedData>) -> Result<TypedData, Vec<TypedData>> {
+    if values.is_empty()
+        || !CURRENT
+            .try_with(|c| c.capabilities.typed_data_collection)
+            .unwrap_or(false)
+    {
+        return Err(values);
+    }
+
+    let is_string = |v: &TypedData| matches!(v.data, Some(Data::String(_)));
+    let is_bytes = |v: &TypedData| matches!(v.data, Some(Data::Bytes(_)) | Some(Data::Stream(_)));
+
+    if values.iter().all(is_string) {
+        return Ok(TypedData {
+            data: Some(Data::CollectionString(CollectionString {
+                string: values
+                    .into_iter()
+                    .filter_map(|v| match v.data {
+                        Some(Data::String(s)) => Some(s),
+                        _ => None,
+                    })
+                    .collect(),
+            })),
+        });
+    }
+
+    if values.iter().all(is_bytes) {
+        return Ok(TypedData {
+            data: Some(Data::CollectionBytes(CollectionBytes {
+                bytes: values
+                    .into_iter()
+                    .filter_map(|v| match v.data {
+                        Some(Data::Bytes(b)) | Some(Data::Stream(b)) => Some(b),
+                        _ => None,
+                    })
+                    .collect(),
+            })),
+        });
+    }
+
+    Err(values)
+}
+
+#[cfg(feature = "testing")]
+pub fn string_data<S: Into<String>>(s: S) -> TypedData {
+    TypedData {
+        data: Some(Data::String(s.into())),
+    }
+}
+
+#[cfg(feature = "testing")]
+pub fn int_data<I: Into<i64>>(i: I) -> TypedData {
+    TypedData {
+        data: Some(Data::Int(i.into())),
+    }
+}
+
+#[cfg(feature = "testing")]
+pub fn json_data<T: serde::Serialize + ?Sized>(value: &T) -> TypedData {
+    TypedData {
+        data: Some(Data::Json(
+            serde_json::to_string(value).expect("failed to serialize JSON data"),
+        )),
+    }
+}
+
+#[cfg(feature = "testing")]
+pub fn time_data(time: DateTime<Utc>) -> TypedData {
+    string_data(time.to_rfc3339())
+}
+
 pub fn deserialize_datetime<'a, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
     D: Deserializer<'a>,
@@ -116,6 +212,42 @@ mod tests {
         assert_eq!(d, DATA);
     }
 
+    #[test]
+    fn it_converts_an_error_to_an_exception() {
+        use std::fmt;
+
+        #[derive(Debug)]
+        struct TestError(&'static str, Option<Box<TestError>>);
+
+        impl fmt::Display for TestError {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl StdError for TestError {
+            fn source(&self) -> Option<&(dyn StdError + 'static)> {
+                self.1.as_ref().map(|e| &**e as &(dyn StdError + 'static))
+            }
+        }
+
+        let error = TestError(
+            "outer",
+            Some(Box::new(TestError(
+                "middle",
+                Some(Box::new(TestError("inner", None))),
+            ))),
+        );
+
+        let exception = to_exception(&error, "example");
+        assert_eq!(exception.message, "outer");
+        assert_eq!(exception.source, "example");
+        assert_eq!(
+            exception.stack_trace,
+            "Caused by: middle\nCaused by: inner\n"
+        );
+    }
+
     #[test]
     fn it_converts_from_double_data() {
         const DATA: f64 = 42.24;
@@ -127,4 +259,55 @@ mod tests {
         let d: f64 = convert_from(&data).unwrap();
         assert_eq!(d, DATA);
     }
+
+    #[test]
+    fn it_converts_values_to_collections_when_negotiated() {
+        use crate::{context::Context, rpc::InvocationRequest, worker::Capabilities};
+
+        let strings = || vec![string("a"), string("b")];
+        let bytes = || {
+            vec![TypedData {
+                data: Some(Data::Bytes(vec![1, 2, 3])),
+            }]
+        };
+
+        assert_eq!(into_collection(strings()), Err(strings()));
+
+        let context = Context::new(
+            &InvocationRequest {
+                invocation_id: "1234".to_owned(),
+                ..Default::default()
+            },
+            "foo",
+            Capabilities {
+                typed_data_collection: true,
+                ..Default::default()
+            },
+        );
+
+        context.in_scope(|| {
+            assert_eq!(
+                into_collection(strings()).unwrap().data,
+                Some(Data::CollectionString(CollectionString {
+                    string: vec!["a".to_owned(), "b".to_owned()],
+                }))
+            );
+            assert_eq!(
+                into_collection(bytes()).unwrap().data,
+                Some(Data::CollectionBytes(CollectionBytes {
+                    bytes: vec![vec![1, 2, 3]],
+                }))
+            );
+
+            let mixed = vec![string("a"), bytes().remove(0)];
+            assert_eq!(into_collection(mixed.clone()), Err(mixed));
+            assert_eq!(into_collection(Vec::new()), Err(Vec::new()));
+        });
+    }
+
+    fn string(s: &str) -> TypedData {
+        TypedData {
+            data: Some(Data::String(s.to_owned())),
+        }
+    }
 }
@@ -1,53 +1,308 @@
 use crate::{
     backtrace::Backtrace,
     codegen::{Function, InvokerFn},
-    context::Context,
-    logger,
+    concurrency,
+    context::{CancellationToken, Context, UNKNOWN_FUNCTION},
+    hooks::Hooks,
+    logger, metrics, middleware,
     registry::Registry,
     rpc::{
-        client::FunctionRpcClient, status_result::Status, streaming_message::Content,
-        FunctionLoadRequest, FunctionLoadResponse, InvocationRequest, InvocationResponse,
-        StartStream, StatusResult, StreamingMessage, WorkerInitResponse, WorkerStatusRequest,
-        WorkerStatusResponse,
+        function_rpc_client::FunctionRpcClient, status_result::Status, streaming_message::Content,
+        CloseSharedMemoryResourcesRequest, CloseSharedMemoryResourcesResponse,
+        FunctionEnvironmentReloadRequest, FunctionEnvironmentReloadResponse, FunctionLoadRequest,
+        FunctionLoadResponse, FunctionMetadataResponse, FunctionsMetadataRequest, InvocationCancel,
+        InvocationRequest, InvocationResponse, RpcException, StartStream, StatusResult,
+        StreamingMessage, WorkerInitRequest, WorkerInitResponse, WorkerStatusRequest,
+        WorkerStatusResponse, WorkerTerminate,
     },
+    shared_memory,
+    trace::{self, INVOCATION_SPAN},
+    util::to_exception,
+};
+use futures::{
+    channel::{
+        mpsc::{unbounded, UnboundedReceiver},
+        oneshot,
+    },
+    future::{abortable, ready, select, AbortHandle, Abortable, BoxFuture, Either, FutureExt},
+    pin_mut,
+    stream::{self, StreamExt},
 };
-use futures::{channel::mpsc::unbounded, future::FutureExt, stream::StreamExt};
 use http::uri::Uri;
-use log::error;
+use log::{error, info, warn};
+use prost::Message;
 use std::{
     cell::RefCell,
+    collections::HashMap,
+    env,
     future::Future,
     panic::{catch_unwind, set_hook, AssertUnwindSafe, PanicInfo},
     pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, Once,
+    },
     task::Poll,
+    time::{Duration, Instant},
 };
 use tokio::future::poll_fn;
 use tokio_executor::threadpool::blocking;
-use tonic::Request;
+use tokio_stream::StreamExt as _;
+use tonic::{transport::Endpoint, Request};
+use tracing::{info_span, Span};
 
 pub type Sender = futures::channel::mpsc::UnboundedSender<StreamingMessage>;
 
-struct ContextFuture<F> {
-    inner: F,
-    invocation_id: String,
-    function_id: String,
+static INIT_LOGGER: Once = Once::new();
+
+// The maximum length of a message sent to the host; zero if there is no limit
+static MAX_MESSAGE_LENGTH: AtomicUsize = AtomicUsize::new(0);
+// The length of the header gRPC prefixes to every message
+const GRPC_HEADER_LENGTH: usize = 5;
+
+thread_local!(static PANIC: RefCell<Option<RpcException>> = RefCell::new(None));
+
+#[derive(Clone)]
+pub(crate) struct Invocation {
+    cancellation_token: CancellationToken,
+    abort_handle: AbortHandle,
     function_name: &'static str,
+    started: Instant,
+}
+
+pub(crate) type Invocations = Arc<Mutex<HashMap<String, Invocation>>>;
+
+// The capability that tells the host the worker reports the metadata of its functions
+pub(crate) const WORKER_INDEXING_CAPABILITY: &str = "WorkerIndexing";
+// The capability that tells the host the worker accepts and sends typed data collections
+const TYPED_DATA_COLLECTION_CAPABILITY: &str = "TypedDataCollection";
+// The capability that tells the host the worker transfers large binding data through shared memory
+const SHARED_MEMORY_DATA_TRANSFER_CAPABILITY: &str = "SharedMemoryDataTransfer";
+
+// The capabilities advertised to the host upon initialization
+//
+// Capabilities that only change the data sent by the host are always in effect, as the
+// bindings accept both forms of the data (e.g. HTTP bodies as bytes or strings).
+const WORKER_CAPABILITIES: &[&str] = &[
+    "RawHttpBodyBytes",
+    "RpcHttpBodyOnly",
+    "RpcHttpTriggerMetadataRemoved",
+    "IgnoreEmptyValuedRpcHttpHeaders",
+    "UseNullableValueDictionaryForHttp",
+    "HandlesWorkerTerminateMessage",
+    "HandlesInvocationCancelMessage",
+    "WorkerStatus",
+    TYPED_DATA_COLLECTION_CAPABILITY,
+    SHARED_MEMORY_DATA_TRANSFER_CAPABILITY,
+    WORKER_INDEXING_CAPABILITY,
+];
+
+// Represents the capabilities negotiated between the worker and the host
+//
+// Binding conversions use the negotiated capabilities to decide what data the host accepts.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Capabilities {
+    // Whether output bindings may be sent as typed data collections
+    pub(crate) typed_data_collection: bool,
+    // Whether output bindings may be sent through shared memory
+    pub(crate) shared_memory_data_transfer: bool,
+}
+
+impl Capabilities {
+    // Negotiates the capabilities with those advertised by the host
+    //
+    // A capability is only in effect if both the worker and the host advertise it.
+    pub(crate) fn negotiate(host: &HashMap<String, String>) -> Self {
+        let supports = |name: &str| {
+            WORKER_CAPABILITIES.contains(&name)
+                && host
+                    .get(name)
+                    .map_or(false, |v| v.eq_ignore_ascii_case("true"))
+        };
+
+        Capabilities {
+            typed_data_collection: supports(TYPED_DATA_COLLECTION_CAPABILITY),
+            // Shared memory is backed by memory-mapped files only on Unix
+            shared_memory_data_transfer: cfg!(unix)
+                && supports(SHARED_MEMORY_DATA_TRANSFER_CAPABILITY),
+        }
+    }
+
+    // Gets the capabilities the worker advertises to the host
+    pub(crate) fn advertised() -> HashMap<String, String> {
+        WORKER_CAPABILITIES
+            .iter()
+            .map(|c| ((*c).to_owned(), "true".to_owned()))
+            .collect()
+    }
+}
+
+// The grace period used when the worker is interrupted rather than terminated by the host
+const INTERRUPT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+// How long aborted invocations are given to respond before they are reported as cancelled
+const ABORT_TIMEOUT: Duration = Duration::from_secs(1);
+// How long the worker waits for the host to close the stream once every message has been sent
+const STREAM_CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+// The number of consecutive attempts made to reach the host before the worker gives up
+const MAX_CONNECT_ATTEMPTS: u32 = 8;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+// Represents the messages sent to the host, shared by every connection to the host
+#[derive(Clone)]
+struct Outgoing {
+    worker_id: String,
+    receiver: Arc<tokio::sync::Mutex<UnboundedReceiver<StreamingMessage>>>,
+    flushed: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+}
+
+impl Outgoing {
+    // Creates the stream of messages for a new connection to the host
+    //
+    // Every stream starts with a start stream message, followed by the queued messages.
+    // Messages queued while reconnecting are sent once the new stream has started.
+    fn stream(&self) -> impl tokio_stream::Stream<Item = StreamingMessage> + Send + 'static {
+        let start = StreamingMessage {
+            content: Some(Content::StartStream(StartStream {
+                worker_id: self.worker_id.clone(),
+            })),
+            ..Default::default()
+        };
+
+        let messages = stream::once(ready(start)).chain(stream::unfold(self.clone(), |outgoing| {
+            async move {
+                let message = outgoing.receiver.lock().await.next().await;
+                match message {
+                    Some(message) => Some((message, outgoing)),
+                    None => {
+                        // Signal once every queued message has been handed to the host stream
+                        // This allows shutdown to wait for pending responses and logs to be sent
+                        if let Some(flushed) = outgoing.flushed.lock().unwrap().take() {
+                            flushed.send(()).unwrap_or(());
+                        }
+                        None
+                    }
+                }
+            }
+        }));
+
+        TonicStream(messages.boxed())
+    }
+}
+
+// Adapts a `futures` preview stream to the `Stream` trait expected by tonic
+struct TonicStream<S>(S);
+
+impl<S: stream::Stream + Unpin> tokio_stream::Stream for TonicStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context,
+    ) -> Poll<Option<Self::Item>> {
+        self.0.poll_next_unpin(cx)
+    }
+}
+
+// Represents an error communicating with the host
+enum StreamError {
+    // The connection was lost or could not be established; the worker reconnects
+    Transient(String),
+    // The worker cannot continue
+    Fatal(String),
+}
+
+impl StreamError {
+    fn from_status(context: &str, status: tonic::Status) -> Self {
+        use tonic::Code;
+
+        let message = format!("{}: {}", context, status);
+        match status.code() {
+            Code::Unavailable
+            | Code::Unknown
+            | Code::Internal
+            | Code::Cancelled
+            | Code::DeadlineExceeded
+            | Code::ResourceExhausted
+            | Code::Aborted => StreamError::Transient(message),
+            _ => StreamError::Fatal(message),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct State {
+    invocations: Invocations,
+    capabilities: Arc<Mutex<Capabilities>>,
+    // Set once the startup hooks have completed successfully
+    initialized: Arc<AtomicBool>,
+    // The result reported to the host when the startup hooks failed
+    startup_failure: Arc<Mutex<Option<StatusResult>>>,
+    shutting_down: Arc<AtomicBool>,
+    // The shutdown in progress, awaited before the worker stops
+    shutdown: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    hooks: Arc<Hooks>,
+}
+
+fn to_duration(duration: Option<prost_types::Duration>) -> Duration {
+    duration
+        .map(|d| Duration::new(d.seconds.max(0) as u64, d.nanos.max(0) as u32))
+        .unwrap_or_default()
+}
+
+// Creates the span the worker opens for an invocation
+fn invocation_span(func: &'static Function, context: &Context) -> Span {
+    info_span!(
+        INVOCATION_SPAN,
+        function = &*func.name,
+        invocation_id = &*context.invocation_id,
+        trace_parent = context.trace_parent().unwrap_or_default(),
+        trigger = func
+            .bindings
+            .iter()
+            .find(|b| b.is_trigger())
+            .and_then(|b| b.binding_type())
+            .unwrap_or_default()
+    )
+}
+
+struct ContextFuture<F> {
+    inner: Abortable<F>,
+    context: Context,
+    span: Span,
+    invocations: Invocations,
     sender: Sender,
 }
 
-impl<F> ContextFuture<F> {
+impl<F: Future<Output = InvocationResponse>> ContextFuture<F> {
     pub fn new(
         inner: F,
-        invocation_id: String,
-        function_id: String,
-        function_name: &'static str,
+        context: Context,
+        func: &'static Function,
+        span: Span,
+        invocations: Invocations,
         sender: Sender,
     ) -> Self {
+        let (inner, abort_handle) = abortable(inner);
+
+        invocations.lock().unwrap().insert(
+            context.invocation_id.clone(),
+            Invocation {
+                cancellation_token: context.cancellation_token.clone(),
+                abort_handle,
+                function_name: context.function_name,
+                started: Instant::now(),
+            },
+        );
+
+        metrics::started(&func.name);
+
         ContextFuture {
             inner,
-            invocation_id,
-            function_id,
-            function_name,
+            context,
+            span,
+            invocations,
             sender,
         }
     }
@@ -56,25 +311,75 @@ impl<F> ContextFuture<F> {
 impl<F: Future<Output = InvocationResponse> + Unpin> Future for ContextFuture<F> {
     type Output = ();
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context) -> Poll<Self::Output> {
-        let _guard = Context::set(&self.invocation_id, &self.function_id, self.function_name);
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context) -> Poll<Self::Output> {
+        let context = self.context.clone();
+        context.in_scope(|| self.poll_invocation(cx))
+    }
+}
 
+impl<F: Future<Output = InvocationResponse> + Unpin> ContextFuture<F> {
+    fn poll_invocation(mut self: Pin<&mut Self>, cx: &mut std::task::Context) -> Poll<()> {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
+        let mut panicked = false;
         let res = match catch_unwind(AssertUnwindSafe(|| self.inner.poll_unpin(cx))) {
             Ok(p) => match p {
-                Poll::Ready(res) => res,
+                Poll::Ready(Ok(res)) => res,
+                Poll::Ready(Err(_)) => InvocationResponse {
+                    invocation_id: self.context.invocation_id.clone(),
+                    result: Some(StatusResult {
+                        status: Status::Cancelled as i32,
+                        result: "Azure Function invocation was cancelled by the host.".to_string(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
                 Poll::Pending => return Poll::Pending,
             },
-            Err(_) => InvocationResponse {
-                invocation_id: self.invocation_id.clone(),
-                result: Some(StatusResult {
-                    status: Status::Failure as i32,
-                    result: "Azure Function panicked: see log for more information.".to_string(),
+            Err(_) => {
+                let exception = PANIC.with(|p| p.borrow_mut().take());
+                panicked = true;
+
+                InvocationResponse {
+                    invocation_id: self.context.invocation_id.clone(),
+                    result: Some(StatusResult {
+                        status: Status::Failure as i32,
+                        result: exception
+                            .as_ref()
+                            .map(|e| e.message.clone())
+                            .unwrap_or_else(|| {
+                                "Azure Function panicked: see log for more information.".to_string()
+                            }),
+                        exception,
+                        ..Default::default()
+                    }),
                     ..Default::default()
-                }),
-                ..Default::default()
-            },
+                }
+            }
         };
 
+        let res = Worker::limit_message_length(self.context.function_name, res);
+
+        // A shutdown that gave up waiting on the invocation has already responded for it
+        // and recorded its metrics
+        let invocation = match self
+            .invocations
+            .lock()
+            .unwrap()
+            .remove(&self.context.invocation_id)
+        {
+            Some(invocation) => invocation,
+            None => return Poll::Ready(()),
+        };
+
+        metrics::completed(
+            invocation.function_name,
+            &res,
+            panicked,
+            invocation.started.elapsed(),
+        );
+
         self.sender
             .unbounded_send(StreamingMessage {
                 content: Some(Content::InvocationResponse(res)),
@@ -89,111 +394,383 @@ impl<F: Future<Output = InvocationResponse> + Unpin> Future for ContextFuture<F>
 pub struct Worker;
 
 impl Worker {
-    pub fn run(host: &str, port: u16, worker_id: &str, mut registry: Registry<'static>) {
-        let host_uri: Uri = format!("http://{0}:{1}", host, port).parse().unwrap();
+    pub fn run(
+        host: &str,
+        port: u16,
+        worker_id: &str,
+        max_message_length: Option<usize>,
+        registry: Registry<'static>,
+        hooks: Hooks,
+    ) -> Result<(), String> {
+        let host_uri: Uri = format!("http://{0}:{1}", host, port)
+            .parse()
+            .map_err(|e| format!("invalid host address '{}:{}': {}", host, port, e))?;
         let (sender, receiver) = unbounded::<StreamingMessage>();
+        let state = State {
+            hooks: Arc::new(hooks),
+            ..Default::default()
+        };
 
-        tokio::runtime::Runtime::new().unwrap().block_on(async {
-            let mut client = FunctionRpcClient::connect(host_uri)
-                .await
-                .map_err(|e| panic!("failed to connect to host: {}", e))
-                .unwrap();
-
-            // Start by sending a start stream message to the channel
-            // This will be sent to the host upon connection
-            sender
-                .unbounded_send(StreamingMessage {
-                    content: Some(Content::StartStream(StartStream {
-                        worker_id: worker_id.to_owned(),
-                    })),
-                    ..Default::default()
-                })
-                .unwrap();
-
-            let mut stream = client
-                .event_stream(Request::new(receiver))
-                .await
-                .map_err(|e| panic!("failed to start event stream: {}", e))
-                .unwrap()
-                .into_inner();
-
-            let init_req = stream
-                .next()
-                .await
-                .expect("expected a worker init request")
-                .map_err(|e| panic!("failed to read event stream response: {}", e))
-                .unwrap();
-
-            Worker::handle_worker_init_request(sender.clone(), init_req).await;
-
-            stream
-                .for_each(move |req| {
-                    Worker::handle_request(
-                        &mut registry,
-                        sender.clone(),
-                        req.expect("expected a request"),
-                    );
-                    futures::future::ready(())
-                })
-                .await;
-        });
+        let runtime = tokio::runtime::Runtime::new()
+            .map_err(|e| format!("failed to create the worker runtime: {}", e))?;
+
+        {
+            let handle = runtime.handle().clone();
+            let state = state.clone();
+            let sender = sender.clone();
+
+            ctrlc::set_handler(move || {
+                Worker::begin_shutdown(&handle, &state, sender.clone(), INTERRUPT_GRACE_PERIOD);
+            })
+            .expect("failed setting SIGINT handler");
+        }
+
+        MAX_MESSAGE_LENGTH.store(max_message_length.unwrap_or(0), Ordering::SeqCst);
+
+        runtime.block_on(Worker::serve(
+            host_uri, worker_id, registry, state, sender, receiver,
+        ))
     }
 
-    async fn handle_worker_init_request(sender: Sender, req: StreamingMessage) {
-        match req.content {
-            Some(Content::WorkerInitRequest(req)) => {
-                println!(
-                    "Connected to Azure Functions host version {}.",
-                    req.host_version
-                );
+    // Connects to the host and processes requests on the current runtime
+    #[cfg(feature = "testing")]
+    pub(crate) async fn connect(
+        host_uri: Uri,
+        worker_id: &str,
+        max_message_length: Option<usize>,
+        registry: Registry<'static>,
+    ) -> Result<(), String> {
+        let (sender, receiver) = unbounded::<StreamingMessage>();
 
-                // TODO: use the level requested by the Azure functions host
-                log::set_boxed_logger(Box::new(logger::Logger::new(
-                    log::Level::Info,
-                    sender.clone(),
-                )))
-                .expect("failed to set the global logger instance");
+        MAX_MESSAGE_LENGTH.store(max_message_length.unwrap_or(0), Ordering::SeqCst);
+
+        Worker::serve(
+            host_uri,
+            worker_id,
+            registry,
+            State::default(),
+            sender,
+            receiver,
+        )
+        .await
+    }
 
-                set_hook(Box::new(Worker::handle_panic));
+    // Processes requests from the host, reconnecting with backoff when the connection is lost
+    async fn serve(
+        host_uri: Uri,
+        worker_id: &str,
+        mut registry: Registry<'static>,
+        state: State,
+        sender: Sender,
+        receiver: UnboundedReceiver<StreamingMessage>,
+    ) -> Result<(), String> {
+        // Log messages are queued for the host until the worker has connected
+        Worker::initialize(sender.clone(), logger::LogLevels::default());
 
-                log::set_max_level(log::LevelFilter::Trace);
+        let (flushed_sender, mut flushed) = oneshot::channel::<()>();
+        let outgoing = Outgoing {
+            worker_id: worker_id.to_owned(),
+            receiver: Arc::new(tokio::sync::Mutex::new(receiver)),
+            flushed: Arc::new(Mutex::new(Some(flushed_sender))),
+        };
 
-                sender
-                    .unbounded_send(StreamingMessage {
-                        content: Some(Content::WorkerInitResponse(WorkerInitResponse {
-                            worker_version: env!("CARGO_PKG_VERSION").to_owned(),
-                            result: Some(StatusResult {
-                                status: Status::Success as i32,
-                                ..Default::default()
-                            }),
-                            ..Default::default()
-                        })),
+        let mut attempts = 0;
+        let result = loop {
+            let session = Worker::run_session(
+                &host_uri,
+                &mut registry,
+                &state,
+                &sender,
+                &outgoing,
+                &mut flushed,
+                &mut attempts,
+            )
+            .await;
+
+            // The host no longer closes the memory maps of the session once it has ended
+            shared_memory::close_all(&shared_memory::directories());
+
+            let err = match session {
+                Ok(()) => break Ok(()),
+                Err(StreamError::Fatal(e)) => break Err(e),
+                Err(StreamError::Transient(e)) => e,
+            };
+
+            // A lost connection cannot be recovered once the worker is shutting down
+            if state.shutting_down.load(Ordering::SeqCst) {
+                break Ok(());
+            }
+
+            attempts += 1;
+            if attempts >= MAX_CONNECT_ATTEMPTS {
+                break Err(format!(
+                    "{} (gave up after {} attempts to reach the host)",
+                    err, attempts
+                ));
+            }
+
+            let backoff = INITIAL_BACKOFF
+                .checked_mul(1 << (attempts - 1))
+                .map_or(MAX_BACKOFF, |b| b.min(MAX_BACKOFF));
+
+            warn!("{}; reconnecting in {:?}.", err, backoff);
+            tokio::time::sleep(backoff).await;
+        };
+
+        if let Err(e) = &result {
+            // The host can no longer be reached, so log messages fall back to stderr
+            sender.close_channel();
+            let mut receiver = outgoing.receiver.lock().await;
+            while let Ok(Some(message)) = receiver.try_next() {
+                if let Some(Content::RpcLog(log)) = message.content {
+                    logger::write_stderr(&log);
+                }
+            }
+
+            error!("Rust worker failed: {}", e);
+        }
+
+        // Finish a shutdown that is still in progress rather than stopping mid-drain
+        let shutdown = state.shutdown.lock().unwrap().take();
+        match shutdown {
+            Some(shutdown) => shutdown.await.unwrap_or(()),
+            // Shutdown hooks have not run if the worker stopped without being terminated
+            None => {
+                if state.initialized.load(Ordering::SeqCst)
+                    && !state.shutting_down.swap(true, Ordering::SeqCst)
+                {
+                    state.hooks.shutdown().await;
+                }
+            }
+        }
+
+        result
+    }
+
+    // Runs a session with the host over a new connection
+    //
+    // The session ends when the host closes the stream or, during shutdown, once every queued
+    // message has been sent to the host.
+    async fn run_session(
+        host_uri: &Uri,
+        registry: &mut Registry<'static>,
+        state: &State,
+        sender: &Sender,
+        outgoing: &Outgoing,
+        flushed: &mut oneshot::Receiver<()>,
+        attempts: &mut u32,
+    ) -> Result<(), StreamError> {
+        // Adaptive flow control lets large payloads use the available bandwidth
+        let channel = Endpoint::from(host_uri.clone())
+            .http2_adaptive_window(true)
+            .connect()
+            .await
+            .map_err(|e| StreamError::Transient(format!("failed to connect to host: {}", e)))?;
+
+        let mut client = FunctionRpcClient::new(channel);
+
+        let mut stream = client
+            .event_stream(Request::new(outgoing.stream()))
+            .await
+            .map_err(|e| StreamError::from_status("failed to start event stream", e))?
+            .into_inner();
+
+        let requests = async {
+            while let Some(req) = stream.next().await {
+                let req = req.map_err(|e| {
+                    StreamError::from_status("failed to read event stream request", e)
+                })?;
+
+                // The connection is healthy once the host has sent a request
+                *attempts = 0;
+
+                match req.content {
+                    Some(Content::WorkerInitRequest(req)) => {
+                        Worker::handle_worker_init_request(state, sender.clone(), req).await
+                    }
+                    content => Worker::handle_request(registry, state, sender.clone(), content),
+                }
+            }
+
+            if state.initialized.load(Ordering::SeqCst) {
+                Ok(())
+            } else if let Some(failure) = state.startup_failure.lock().unwrap().as_ref() {
+                Err(StreamError::Fatal(failure.result.clone()))
+            } else {
+                Err(StreamError::Transient(
+                    "the host closed the event stream before initializing the worker".to_owned(),
+                ))
+            }
+        };
+
+        pin_mut!(requests);
+
+        // Run until the host closes the stream or a shutdown has flushed all messages
+        match select(requests, flushed).await {
+            Either::Left((result, _)) => result,
+            Either::Right((_, requests)) => {
+                // Dropping the stream right away would reset it before the host has read the
+                // last messages, so give the host a moment to close the stream first
+                tokio::time::timeout(STREAM_CLOSE_TIMEOUT, requests)
+                    .await
+                    .unwrap_or(Ok(()))
+                    .unwrap_or(());
+                Ok(())
+            }
+        }
+    }
+
+    async fn handle_worker_init_request(state: &State, sender: Sender, req: WorkerInitRequest) {
+        println!(
+            "Connected to Azure Functions host version {}.",
+            req.host_version
+        );
+
+        Worker::initialize(
+            sender.clone(),
+            logger::LogLevels::from_host(&req.log_categories),
+        );
+
+        let capabilities = Capabilities::negotiate(&req.capabilities);
+        info!("Negotiated worker capabilities: {:?}", capabilities);
+        *state.capabilities.lock().unwrap() = capabilities;
+
+        // Startup hooks run once, before any functions are loaded by the host
+        // The host initializes the worker again if the worker reconnects; a failed startup is
+        // reported again rather than retried
+        let failure = state.startup_failure.lock().unwrap().clone();
+        let result = if state.initialized.load(Ordering::SeqCst) {
+            StatusResult {
+                status: Status::Success as i32,
+                ..Default::default()
+            }
+        } else if let Some(failure) = failure {
+            failure
+        } else {
+            match state.hooks.start().await {
+                Ok(()) => {
+                    state.initialized.store(true, Ordering::SeqCst);
+
+                    StatusResult {
+                        status: Status::Success as i32,
                         ..Default::default()
-                    })
-                    .unwrap();
+                    }
+                }
+                Err(e) => {
+                    error!("Rust worker startup hook failed: {}", e);
+
+                    let failure = StatusResult {
+                        status: Status::Failure as i32,
+                        result: format!("Rust worker startup hook failed: {}", e),
+                        exception: Some(to_exception(&*e, "Rust worker")),
+                        ..Default::default()
+                    };
+
+                    *state.startup_failure.lock().unwrap() = Some(failure.clone());
+                    failure
+                }
             }
-            _ => panic!("expected a worker init request message from the host"),
         };
+
+        sender
+            .unbounded_send(StreamingMessage {
+                content: Some(Content::WorkerInitResponse(WorkerInitResponse {
+                    worker_version: env!("CARGO_PKG_VERSION").to_owned(),
+                    capabilities: Capabilities::advertised(),
+                    result: Some(result),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            })
+            .unwrap_or(());
     }
 
-    fn handle_request(registry: &mut Registry<'static>, sender: Sender, req: StreamingMessage) {
-        match req.content {
+    // Sets up logging and panic handling for invocations
+    pub(crate) fn initialize(sender: Sender, mut levels: logger::LogLevels) {
+        levels.apply_environment(env::vars());
+        logger::set_levels(levels);
+
+        // The global logger can only be set once; later workers (e.g. when testing)
+        // replace the sender that log messages are sent to
+        logger::set_sender(sender);
+        INIT_LOGGER.call_once(|| {
+            log::set_boxed_logger(Box::new(logger::Logger))
+                .expect("failed to set the global logger instance");
+        });
+
+        set_hook(Box::new(Worker::handle_panic));
+    }
+
+    fn handle_request(
+        registry: &mut Registry<'static>,
+        state: &State,
+        sender: Sender,
+        content: Option<Content>,
+    ) {
+        match content {
+            Some(Content::FunctionsMetadataRequest(req)) => {
+                Worker::handle_functions_metadata_request(registry, sender, req)
+            }
             Some(Content::FunctionLoadRequest(req)) => {
                 Worker::handle_function_load_request(registry, sender, req)
             }
             Some(Content::InvocationRequest(req)) => {
-                Worker::handle_invocation_request(registry, sender, req)
+                Worker::handle_invocation_request(registry, state, sender, req)
             }
             Some(Content::WorkerStatusRequest(req)) => {
                 Worker::handle_worker_status_request(sender, req)
             }
             Some(Content::FileChangeEventRequest(_)) => {}
-            Some(Content::InvocationCancel(_)) => {}
-            Some(Content::FunctionEnvironmentReloadRequest(_)) => {}
-            _ => panic!("unexpected message from host: {:?}.", req),
+            Some(Content::CloseSharedMemoryResourcesRequest(req)) => {
+                Worker::handle_close_shared_memory_resources_request(sender, req)
+            }
+            Some(Content::InvocationCancel(req)) => {
+                Worker::handle_invocation_cancel(&state.invocations, req)
+            }
+            Some(Content::WorkerTerminate(req)) => {
+                Worker::handle_worker_terminate(state, sender, req)
+            }
+            Some(Content::FunctionEnvironmentReloadRequest(req)) => {
+                Worker::handle_function_environment_reload_request(sender, req)
+            }
+            // Messages added to the protocol after this worker was built have no known content
+            None => warn!("Rust worker ignored a message from the host with unknown content."),
+            Some(content) => warn!(
+                "Rust worker ignored an unexpected message from the host: {:?}.",
+                content
+            ),
         };
     }
 
+    fn handle_functions_metadata_request(
+        registry: &Registry<'static>,
+        sender: Sender,
+        req: FunctionsMetadataRequest,
+    ) {
+        // The host starts the worker executable, so it is reported as the functions' script file
+        let script_file = env::current_exe()
+            .ok()
+            .and_then(|p| p.file_name().map(|f| f.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+
+        sender
+            .unbounded_send(StreamingMessage {
+                content: Some(Content::FunctionMetadataResponse(
+                    FunctionMetadataResponse {
+                        function_metadata_results: registry
+                            .build_metadata(&req.function_app_directory, &script_file),
+                        result: Some(StatusResult {
+                            status: Status::Success as i32,
+                            ..Default::default()
+                        }),
+                        use_default_metadata_indexing: false,
+                    },
+                )),
+                ..Default::default()
+            })
+            .expect("failed to send function metadata response");
+    }
+
     fn handle_function_load_request(
         registry: &mut Registry<'static>,
         sender: Sender,
@@ -230,15 +807,22 @@ impl Worker {
 
     fn handle_invocation_request(
         registry: &Registry<'static>,
+        state: &State,
         sender: Sender,
         req: InvocationRequest,
     ) {
-        if let Some(func) = registry.get(&req.function_id) {
-            Worker::invoke_function(func, sender, req);
+        let startup_failure = state.startup_failure.lock().unwrap().clone();
+        let error = if let Some(failure) = startup_failure {
+            failure.result
+        } else if state.shutting_down.load(Ordering::SeqCst) {
+            "The Rust worker is shutting down.".to_string()
+        } else if let Some(func) = registry.get(&req.function_id) {
+            let capabilities = *state.capabilities.lock().unwrap();
+            Worker::invoke_function(func, state.invocations.clone(), capabilities, sender, req);
             return;
-        }
-
-        let error = format!("Function with id '{}' does not exist.", req.function_id);
+        } else {
+            format!("Function with id '{}' does not exist.", req.function_id)
+        };
 
         sender
             .unbounded_send(StreamingMessage {
@@ -256,7 +840,188 @@ impl Worker {
             .expect("failed to send invocation response");
     }
 
+    fn handle_close_shared_memory_resources_request(
+        sender: Sender,
+        req: CloseSharedMemoryResourcesRequest,
+    ) {
+        sender
+            .unbounded_send(StreamingMessage {
+                content: Some(Content::CloseSharedMemoryResourcesResponse(
+                    CloseSharedMemoryResourcesResponse {
+                        close_map_results: shared_memory::close(&req.map_names),
+                    },
+                )),
+                ..Default::default()
+            })
+            .expect("failed to send close shared memory resources response");
+    }
+
+    fn handle_invocation_cancel(invocations: &Invocations, req: InvocationCancel) {
+        let invocation = match invocations.lock().unwrap().get(&req.invocation_id) {
+            Some(invocation) => invocation.clone(),
+            // The invocation has already completed
+            None => return,
+        };
+
+        warn!(
+            "Azure Functions Host requested cancellation of invocation '{}'.",
+            req.invocation_id
+        );
+
+        invocation.cancellation_token.cancel();
+
+        // Give the function until the end of the grace period to observe the cancellation
+        // If it has not completed by then, the invocation future is aborted
+        let grace_period = to_duration(req.grace_period);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(grace_period).await;
+            invocation.abort_handle.abort();
+        });
+    }
+
+    fn handle_worker_terminate(state: &State, sender: Sender, req: WorkerTerminate) {
+        Worker::begin_shutdown(
+            &tokio::runtime::Handle::current(),
+            state,
+            sender,
+            to_duration(req.grace_period),
+        );
+    }
+
+    // Starts shutting down the worker; the worker waits for the shutdown before it stops
+    fn begin_shutdown(
+        handle: &tokio::runtime::Handle,
+        state: &State,
+        sender: Sender,
+        grace_period: Duration,
+    ) {
+        let mut shutdown = state.shutdown.lock().unwrap();
+        if shutdown.is_none() {
+            *shutdown = Some(handle.spawn(Worker::shutdown(state.clone(), sender, grace_period)));
+        }
+    }
+
+    async fn shutdown(state: State, sender: Sender, grace_period: Duration) {
+        // Stop accepting new invocations; only the first shutdown request is honored
+        if state.shutting_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        info!(
+            "Rust worker is shutting down; waiting up to {:?} for in-flight invocations to complete.",
+            grace_period
+        );
+
+        let deadline = Instant::now() + grace_period;
+        while !state.invocations.lock().unwrap().is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+
+        let remaining: Vec<_> = state
+            .invocations
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
+
+        if !remaining.is_empty() {
+            warn!(
+                "{} invocation(s) did not complete before the shutdown grace period expired.",
+                remaining.len()
+            );
+
+            // Abort the remaining invocations so that they respond as cancelled
+            for invocation in remaining {
+                invocation.cancellation_token.cancel();
+                invocation.abort_handle.abort();
+            }
+
+            // Invocations blocked in synchronous code cannot be aborted, so stop waiting on them
+            let deadline = Instant::now() + ABORT_TIMEOUT;
+            while !state.invocations.lock().unwrap().is_empty() && Instant::now() < deadline {
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            }
+
+            let remaining: Vec<_> = state.invocations.lock().unwrap().drain().collect();
+            for (invocation_id, invocation) in remaining {
+                warn!(
+                    "Invocation '{}' did not respond to being aborted; reporting it as cancelled.",
+                    invocation_id
+                );
+
+                let res = InvocationResponse {
+                    invocation_id,
+                    result: Some(StatusResult {
+                        status: Status::Cancelled as i32,
+                        result: "Azure Function invocation was cancelled by the worker shutdown."
+                            .to_string(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                };
+
+                metrics::completed(
+                    invocation.function_name,
+                    &res,
+                    false,
+                    invocation.started.elapsed(),
+                );
+
+                sender
+                    .unbounded_send(StreamingMessage {
+                        content: Some(Content::InvocationResponse(res)),
+                        ..Default::default()
+                    })
+                    .unwrap_or(());
+            }
+        }
+
+        // Shutdown hooks only run if the startup hooks succeeded
+        if state.initialized.load(Ordering::SeqCst) {
+            state.hooks.shutdown().await;
+        }
+
+        shared_memory::close_all(&shared_memory::directories());
+
+        log::logger().flush();
+
+        // Closing the channel ends the outgoing stream once all queued messages have been sent
+        sender.close_channel();
+    }
+
+    fn handle_function_environment_reload_request(
+        sender: Sender,
+        req: FunctionEnvironmentReloadRequest,
+    ) {
+        for (name, value) in req.environment_variables.iter() {
+            env::set_var(name, value);
+        }
+
+        // Allow host.json logging overrides in the new environment to take effect
+        logger::update_levels(|levels| levels.apply_environment(req.environment_variables));
+
+        sender
+            .unbounded_send(StreamingMessage {
+                content: Some(Content::FunctionEnvironmentReloadResponse(
+                    FunctionEnvironmentReloadResponse {
+                        capabilities: Capabilities::advertised(),
+                        result: Some(StatusResult {
+                            status: Status::Success as i32,
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            })
+            .expect("failed to send function environment reload response");
+    }
+
     fn handle_worker_status_request(sender: Sender, _: WorkerStatusRequest) {
+        metrics::report();
+
         sender
             .unbounded_send(StreamingMessage {
                 content: Some(Content::WorkerStatusResponse(WorkerStatusResponse {})),
@@ -265,7 +1030,210 @@ impl Worker {
             .expect("failed to send worker status response");
     }
 
-    fn invoke_function(func: &'static Function, sender: Sender, req: InvocationRequest) {
+    // Invokes the function; the response is sent with the given sender upon completion
+    pub(crate) fn invoke_function(
+        func: &'static Function,
+        invocations: Invocations,
+        capabilities: Capabilities,
+        sender: Sender,
+        req: InvocationRequest,
+    ) {
+        let context = Context::new(&req, &func.name, capabilities);
+        let span = invocation_span(func, &context);
+        let invocation_span = span.clone();
+
+        let received = Instant::now();
+
+        tokio::spawn(ContextFuture::new(
+            Box::pin(async move {
+                let _permits = concurrency::acquire(func).await;
+                metrics::dequeued(&func.name, received.elapsed());
+
+                // Inputs are read once within the concurrency limits so that queued invocations
+                // do not hold copies of large inputs
+                let mut req = req;
+                if let Err(e) = shared_memory::read_inputs(&mut req, &shared_memory::directories())
+                {
+                    return Worker::failure_response(func, req.invocation_id, e);
+                }
+
+                let invocation_id = req.invocation_id.clone();
+                let invocation = middleware::run(func, req, Worker::call_function);
+
+                // The timeout starts once the invocation is within the concurrency limits
+                let res = match func.timeout {
+                    Some(timeout) => {
+                        match select(invocation, Box::pin(tokio::time::sleep(timeout))).await {
+                            Either::Left((res, _)) => res,
+                            Either::Right((_, invocation)) => {
+                                // The active span must be read before the invocation is dropped
+                                let active_span = trace::active_span(&invocation_span);
+                                drop(invocation);
+                                Worker::timeout_response(func, invocation_id, timeout, active_span)
+                            }
+                        }
+                    }
+                    None => invocation.await,
+                };
+
+                if capabilities.shared_memory_data_transfer {
+                    shared_memory::write_outputs(func, res, &shared_memory::directories())
+                } else {
+                    res
+                }
+            }),
+            context,
+            func,
+            span,
+            invocations,
+            sender,
+        ));
+    }
+
+    // Replaces a response that exceeds the maximum message length with a failure
+    //
+    // The host cannot receive a message that exceeds the limit; the failure names the largest
+    // binding of the response so that it can be identified.
+    fn limit_message_length(function_name: &str, res: InvocationResponse) -> InvocationResponse {
+        let max_length = MAX_MESSAGE_LENGTH.load(Ordering::SeqCst);
+        if max_length == 0 {
+            return res;
+        }
+
+        // tonic 0.7 cannot limit the length of sent messages, so the response is measured as
+        // the host receives it: wrapped in a streaming message and prefixed by the gRPC header
+        let message = StreamingMessage {
+            content: Some(Content::InvocationResponse(res)),
+            ..Default::default()
+        };
+        let length = message.encoded_len() + GRPC_HEADER_LENGTH;
+        let res = match message.content {
+            Some(Content::InvocationResponse(res)) => res,
+            _ => unreachable!(),
+        };
+
+        if length <= max_length {
+            return res;
+        }
+
+        let largest = res
+            .output_data
+            .iter()
+            .map(|b| (b.name.as_str(), b.encoded_len()))
+            .chain(
+                res.return_value
+                    .iter()
+                    .map(|v| ("$return", v.encoded_len())),
+            )
+            .max_by_key(|(_, len)| *len);
+
+        let mut message = format!(
+            "The response of Azure Function '{}' is {} bytes, which exceeds the maximum gRPC message length of {} bytes",
+            function_name, length, max_length
+        );
+        if let Some((binding, len)) = largest {
+            message.push_str(&format!(
+                "; the largest binding is '{}' at {} bytes",
+                binding, len
+            ));
+        }
+        message.push('.');
+
+        error!("{}", message);
+
+        InvocationResponse {
+            invocation_id: res.invocation_id,
+            result: Some(StatusResult {
+                status: Status::Failure as i32,
+                result: message.clone(),
+                exception: Some(RpcException {
+                    source: function_name.to_owned(),
+                    stack_trace: String::new(),
+                    message,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    // Creates the response for an invocation that did not complete within the function's timeout
+    fn timeout_response(
+        func: &'static Function,
+        invocation_id: String,
+        timeout: Duration,
+        active_span: Option<String>,
+    ) -> InvocationResponse {
+        metrics::timed_out(&func.name);
+
+        let message = format!(
+            "Azure Function '{}' timed out after {} seconds.",
+            func.name,
+            timeout.as_secs()
+        );
+
+        let stack_trace = match active_span {
+            Some(span) => {
+                error!("{} The last span entered was {}.", message, span);
+                format!("while in span {}", span)
+            }
+            None => {
+                error!(
+                    "{} Instrument awaited futures with `tracing` spans and add the `azure_functions::trace::Layer` to a `tracing_subscriber::Registry` to log the await point that did not complete.",
+                    message
+                );
+                String::new()
+            }
+        };
+
+        InvocationResponse {
+            invocation_id,
+            result: Some(StatusResult {
+                status: Status::Failure as i32,
+                result: message.clone(),
+                exception: Some(RpcException {
+                    source: func.name.to_string(),
+                    stack_trace,
+                    message,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    // Creates the response for an invocation that failed before the function was called
+    fn failure_response(
+        func: &'static Function,
+        invocation_id: String,
+        message: String,
+    ) -> InvocationResponse {
+        error!("Azure Function '{}' failed: {}", func.name, message);
+
+        InvocationResponse {
+            invocation_id,
+            result: Some(StatusResult {
+                status: Status::Failure as i32,
+                result: message.clone(),
+                exception: Some(RpcException {
+                    source: func.name.to_string(),
+                    stack_trace: String::new(),
+                    message,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    // Calls the function's invoker at the end of the middleware pipeline
+    fn call_function(
+        func: &'static Function,
+        req: InvocationRequest,
+    ) -> BoxFuture<'static, InvocationResponse> {
         match func
             .invoker
             .as_ref()
@@ -275,76 +1243,77 @@ impl Worker {
             InvokerFn::Sync(invoker_fn) => {
                 // `poll_fn` takes FnMut and `blocking` takes FnOnce
                 // Wrap the request with a RefCell so we can move the request to the invoked function
-                let id = req.invocation_id.clone();
-                let func_id = req.function_id.clone();
                 let req = RefCell::new(Some(req));
 
-                tokio::spawn(ContextFuture::new(
-                    poll_fn(move |_| {
-                        blocking(|| {
-                            invoker_fn.expect("invoker must have a callback")(
-                                req.replace(None).expect("only a single call to invoker"),
-                            )
-                        })
+                poll_fn(move |_| {
+                    blocking(|| {
+                        invoker_fn.expect("invoker must have a callback")(
+                            req.replace(None).expect("only a single call to invoker"),
+                        )
                     })
-                    .map(|r| r.expect("expected a response")),
-                    id,
-                    func_id,
-                    &func.name,
-                    sender,
-                ));
-            }
-            InvokerFn::Async(invoker_fn) => {
-                let id = req.invocation_id.clone();
-                let func_id = req.function_id.clone();
-
-                tokio::spawn(ContextFuture::new(
-                    invoker_fn.expect("invoker must have a callback")(req),
-                    id,
-                    func_id,
-                    &func.name,
-                    sender,
-                ));
+                })
+                .map(|r| r.expect("expected a response"))
+                .boxed()
             }
-        };
+            InvokerFn::Async(invoker_fn) => invoker_fn.expect("invoker must have a callback")(req),
+        }
     }
 
     fn handle_panic(info: &PanicInfo) {
         let backtrace = Backtrace::new();
-        match info.location() {
+        let function_name = crate::context::CURRENT
+            .try_with(|c| c.function_name)
+            .unwrap_or(UNKNOWN_FUNCTION);
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .cloned()
+            .unwrap_or_else(|| {
+                info.payload()
+                    .downcast_ref::<String>()
+                    .map(String::as_str)
+                    .unwrap_or("")
+            });
+
+        let stack_trace = match info.location() {
             Some(location) => {
                 error!(
                     "Azure Function '{}' panicked with '{}', {}:{}:{}{}",
-                    crate::context::CURRENT.with(|c| c.borrow().function_name),
-                    info.payload()
-                        .downcast_ref::<&str>()
-                        .cloned()
-                        .unwrap_or_else(|| info
-                            .payload()
-                            .downcast_ref::<String>()
-                            .map(String::as_str)
-                            .unwrap_or("")),
+                    function_name,
+                    payload,
                     location.file(),
                     location.line(),
                     location.column(),
                     backtrace
                 );
+                format!(
+                    "at {}:{}:{}{}",
+                    location.file(),
+                    location.line(),
+                    location.column(),
+                    backtrace
+                )
             }
             None => {
                 error!(
                     "Azure Function '{}' panicked with '{}'{}",
-                    crate::context::CURRENT.with(|c| c.borrow().function_name),
-                    info.payload()
-                        .downcast_ref::<&str>()
-                        .cloned()
-                        .unwrap_or_else(|| info
-                            .payload()
-                            .downcast_ref::<String>()
-                            .map(String::as_str)
-                            .unwrap_or("")),
-                    backtrace
+                    function_name, payload, backtrace
                 );
+                backtrace.to_string().trim_start().to_owned()
             }
         };
+
+        // The panic is reported in the response of the invocation that unwinds on this thread
+        PANIC.with(|p| {
+            *p.borrow_mut() = Some(RpcException {
+                source: function_name.to_owned(),
+                stack_trace,
+                message: format!(
+                    "Azure Function '{}' panicked with '{}'.",
+                    function_name, payload
+                ),
+                ..Default::default()
+            })
+        });
     }
 }
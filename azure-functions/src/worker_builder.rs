@@ -0,0 +1,175 @@
+use crate::{
+    codegen::Function,
+    commands::{Init, Run, Serve, ServeHttp, SyncExtensions},
+    concurrency,
+    hooks::{HookError, Hooks},
+    middleware::{self, Middleware, MiddlewareMap},
+    registry::Registry,
+    state::{self, StateMap},
+};
+use clap::{App, AppSettings};
+use std::{any::TypeId, future::Future, sync::Arc};
+
+/// Builds and runs the Azure Functions for Rust worker.
+///
+/// Use the builder to register state shared by functions, lifecycle hooks, invocation
+/// middleware, concurrency limits and additional binding extensions.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// fn main() {
+///     azure_functions::WorkerBuilder::new(functions::EXPORTS)
+///         .state(Pool::connect("..."))
+///         .on_start(|| async { migrations::run().await })
+///         .on_shutdown(|| async { telemetry::flush().await })
+///         .middleware(Timing)
+///         .named_middleware("auth", Authenticate)
+///         .max_concurrency(64)
+///         .extensions(&[("Microsoft.Azure.WebJobs.Extensions.Kafka", "1.0.0-alpha")])
+///         .run(std::env::args());
+/// }
+/// ```
+pub struct WorkerBuilder<'a> {
+    functions: &'a [&'static Function],
+    extensions: &'a [(&'a str, &'a str)],
+    state: StateMap,
+    hooks: Hooks,
+    middleware: MiddlewareMap,
+    max_concurrency: Option<usize>,
+}
+
+impl<'a> WorkerBuilder<'a> {
+    /// Creates a new worker builder for the given functions.
+    pub fn new(functions: &'a [&'static Function]) -> Self {
+        WorkerBuilder {
+            functions,
+            extensions: &[],
+            state: StateMap::new(),
+            hooks: Hooks::default(),
+            middleware: MiddlewareMap::default(),
+            max_concurrency: None,
+        }
+    }
+
+    /// Sets the additional Azure Function binding extensions used by the functions.
+    pub fn extensions(mut self, extensions: &'a [(&'a str, &'a str)]) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Registers state that is passed to functions with a parameter of type `State<T>`.
+    ///
+    /// State is registered by type; registering state of the same type replaces it.
+    pub fn state<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.state.insert(TypeId::of::<T>(), Arc::new(value));
+        self
+    }
+
+    /// Adds a hook that runs when the worker starts.
+    ///
+    /// Startup hooks run in registration order on the worker's runtime after the worker connects
+    /// to the Azure Functions Host and before any function is loaded. If a hook fails, the
+    /// error is reported to the host as a failure to initialize the worker.
+    pub fn on_start<F, Fut, E>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<HookError>,
+    {
+        self.hooks.add_start(hook);
+        self
+    }
+
+    /// Adds a hook that runs when the worker shuts down.
+    ///
+    /// Shutdown hooks run in reverse registration order on the worker's runtime once in-flight
+    /// invocations have completed. Errors from shutdown hooks are logged.
+    pub fn on_shutdown<F, Fut, E>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<HookError>,
+    {
+        self.hooks.add_shutdown(hook);
+        self
+    }
+
+    /// Adds middleware that runs around every invocation of every function.
+    ///
+    /// Middleware runs in registration order, before any middleware named by the function.
+    pub fn middleware<M: Middleware>(mut self, middleware: M) -> Self {
+        self.middleware.global.push(Arc::new(middleware));
+        self
+    }
+
+    /// Adds middleware that runs around the invocations of functions that name it.
+    ///
+    /// Functions name middleware with the `middleware` argument of the `func` attribute
+    /// (e.g. `#[func(middleware = "auth")]`); named middleware runs in the order it is named.
+    /// The worker fails to start if a function names middleware that has not been added.
+    pub fn named_middleware<M: Middleware>(mut self, name: &str, middleware: M) -> Self {
+        self.middleware
+            .named
+            .insert(name.to_owned(), Arc::new(middleware));
+        self
+    }
+
+    /// Sets the maximum number of invocations the worker runs concurrently.
+    ///
+    /// Invocations over the limit are queued by the worker until a running invocation completes.
+    /// Individual functions can be limited with the `max_concurrency` argument of the `func`
+    /// attribute (e.g. `#[func(max_concurrency = 4)]`); an invocation must be within both limits
+    /// to run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the limit is zero.
+    pub fn max_concurrency(mut self, limit: usize) -> Self {
+        assert!(
+            limit > 0,
+            "the maximum concurrency must be greater than zero."
+        );
+        self.max_concurrency = Some(limit);
+        self
+    }
+
+    /// Runs the worker with the given command line arguments.
+    pub fn run(self, args: impl Iterator<Item = String>) {
+        let registry = Registry::new(self.functions);
+
+        state::register(self.state);
+        middleware::register(self.middleware);
+        concurrency::configure(self.max_concurrency, self.functions);
+
+        if let Err(e) = middleware::validate(self.functions.iter().copied()) {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+
+        let app = App::new("Azure Functions for Rust worker")
+            .version(env!("CARGO_PKG_VERSION"))
+            .about("Implements the Azure Functions for Rust worker.")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .setting(AppSettings::VersionlessSubcommands)
+            .subcommand(Init::create_subcommand())
+            .subcommand(SyncExtensions::create_subcommand())
+            .subcommand(Run::create_subcommand())
+            .subcommand(Serve::create_subcommand())
+            .subcommand(ServeHttp::create_subcommand());
+
+        if let Err(e) = match app.get_matches_from(args).subcommand() {
+            ("init", Some(args)) => Init::from(args).execute(registry, self.extensions),
+            ("sync-extensions", Some(args)) => {
+                SyncExtensions::from(args).execute(registry, self.extensions)
+            }
+            ("run", Some(args)) => Run::from(args).execute(registry, self.hooks),
+            ("serve", Some(args)) => Serve::from(args).execute(registry, self.hooks),
+            ("serve-http", Some(args)) => ServeHttp::from(args).execute(registry, self.hooks),
+            _ => panic!("expected a subcommand."),
+        } {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}